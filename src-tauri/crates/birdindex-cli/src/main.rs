@@ -0,0 +1,588 @@
+mod server;
+
+use anyhow::{anyhow, Result};
+use birdindex_core::aliases::aliases_path;
+use birdindex_core::ioc::IocDatabase;
+use birdindex_core::overrides::{self, OverrideStore};
+use birdindex_core::session::ScanSession;
+use birdindex_core::tree::unidentified_name;
+use birdindex_core::types::{
+    AlphaCode, CombinationNode, ExclusionOptions, ExportFilter, ExportFormat, GalleryLinks,
+    MatchOptions, OverrideAction, PhotoSort, ScanRequest, TreeOptions, TreeSort,
+};
+use birdindex_core::{export, gallery, ioc, scan_and_build, search};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "birdindex-cli", version, about = "Headless BirdIndex2 scanner")]
+struct Cli {
+    /// Path to the Multiling IOC workbook
+    #[arg(long, global = true, default_value = "Multiling IOC 15.1_d.xlsx")]
+    ioc: PathBuf,
+    /// Path to the scan cache file (defaults to the system temp dir)
+    #[arg(long, global = true)]
+    cache: Option<PathBuf>,
+    /// Path to the manual override file (none applied when omitted)
+    #[arg(long, global = true)]
+    overrides: Option<PathBuf>,
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Scan folders and print match statistics
+    Scan(ScanArgs),
+    /// Print the taxonomic tree
+    Tree(ScanArgs),
+    /// List the photos of one species
+    Species {
+        latin: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// Search species names and photo paths
+    Search {
+        query: String,
+        /// Maximum number of hits
+        #[arg(long, default_value_t = search::DEFAULT_LIMIT)]
+        limit: usize,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// List files that did not match any species
+    Unmatched(ScanArgs),
+    /// List files whose match was rejected by --never-match or --veto-word
+    Vetoed(ScanArgs),
+    /// Export a life list, Darwin Core Archive or HTML gallery
+    Export {
+        #[arg(long, value_enum)]
+        format: CliExportFormat,
+        #[arg(long)]
+        output: PathBuf,
+        #[arg(long)]
+        year: Option<i32>,
+        /// Only include photos under this root
+        #[arg(long = "only-root")]
+        only_root: Option<String>,
+        /// Only export this part of the tree to a Darwin Core Archive, as
+        /// Order[/Family[/Genus[/Species]]]
+        #[arg(long)]
+        subtree: Option<String>,
+        /// Use absolute file:// links in the HTML gallery
+        #[arg(long)]
+        absolute_links: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// Serve the index as a read-only JSON API over HTTP
+    Serve {
+        /// Address to listen on; use 0.0.0.0:<port> to allow LAN access
+        #[arg(long, default_value = "127.0.0.1:8765")]
+        bind: String,
+        /// Access token clients must send (generated when omitted)
+        #[arg(long)]
+        token: Option<String>,
+        /// Let clients rescan the roots with `POST /api/scan`
+        #[arg(long)]
+        allow_rescan: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// Compare two IOC workbooks
+    DiffIoc { old: PathBuf, new: PathBuf },
+    /// Look up a banding code, or list ambiguous codes when none is given
+    Code { code: Option<String> },
+    /// Manage manual photo classifications (requires --overrides)
+    #[command(subcommand)]
+    Override(OverrideCommand),
+}
+
+#[derive(Subcommand)]
+enum OverrideCommand {
+    /// Assign a photo to a species, or ignore it
+    Set {
+        path: PathBuf,
+        /// Latin name, optionally with a subspecies epithet
+        #[arg(required_unless_present = "ignore", conflicts_with = "ignore")]
+        latin: Option<String>,
+        /// Leave the photo out of the index
+        #[arg(long)]
+        ignore: bool,
+        /// Key the override on file content so it survives moves and renames
+        #[arg(long)]
+        by_content: bool,
+    },
+    /// Remove the override of a photo
+    Clear { path: PathBuf },
+    /// List all overrides
+    List,
+}
+
+#[derive(Args)]
+struct ScanArgs {
+    /// Photo root folders to scan
+    #[arg(required = true)]
+    roots: Vec<String>,
+    #[arg(long, value_enum, default_value = "ioc")]
+    sort: CliTreeSort,
+    /// Include IOC species without photos
+    #[arg(long)]
+    full_tree: bool,
+    /// Add a subspecies level below each species
+    #[arg(long)]
+    subspecies: bool,
+    /// Leave out photos matched with a lower confidence (0-100)
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    min_confidence: u8,
+    /// Also match Traditional Chinese names in file names
+    #[arg(long)]
+    match_traditional: bool,
+    /// Also match full pinyin (e.g. baitoubei) in file names
+    #[arg(long)]
+    match_pinyin: bool,
+    /// Also match pinyin initials (e.g. btb) in file names; prone to false matches
+    #[arg(long)]
+    match_pinyin_initials: bool,
+    /// Ignore accents when matching file names (é matches e)
+    #[arg(long)]
+    strip_accents: bool,
+    /// Also match 4-letter English and 6-letter Latin banding codes (e.g. LVBU, PYCSIN)
+    #[arg(long)]
+    match_codes: bool,
+    /// Also match English names as whole words (e.g. Light-vented Bulbul)
+    #[arg(long)]
+    match_english: bool,
+    /// Also match Latin names with one typo (e.g. Pycnonotus sinnensis)
+    #[arg(long)]
+    match_fuzzy: bool,
+    /// File name template, e.g. "{date}_{location}_{species}_{seq}" or a regex
+    /// with named groups; repeat to try several in order
+    #[arg(long = "template")]
+    templates: Vec<String>,
+    /// Text that never counts toward a match, e.g. a place name; repeatable
+    #[arg(long)]
+    never_match: Vec<String>,
+    /// Word that rejects any match in the same file name (e.g. not, vs, cf.); repeatable
+    #[arg(long = "veto-word")]
+    veto_words: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CliTreeSort {
+    Ioc,
+    Alphabetical,
+    Count,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CliExportFormat {
+    Csv,
+    Xlsx,
+    Markdown,
+    Dwca,
+    Html,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match &cli.command {
+        Command::Scan(args) => {
+            let session = scan(&cli, args)?;
+            if cli.json {
+                print_json(&session.stats)
+            } else {
+                println!("Scanned files:  {}", session.stats.total_files);
+                println!("Matched files:  {}", session.stats.matched_files);
+                println!("Unmatched:      {}", session.stats.unmatched_files);
+                if session.stats.vetoed_files > 0 {
+                    println!("  vetoed:       {}", session.stats.vetoed_files);
+                }
+                if session.tree.low_confidence > 0 {
+                    println!("Low confidence: {}", session.tree.low_confidence);
+                }
+                println!(
+                    "Species:        {}/{}",
+                    session.tree.photographed_species, session.tree.total_species
+                );
+                Ok(())
+            }
+        }
+        Command::Tree(args) => {
+            let session = scan(&cli, args)?;
+            if cli.json {
+                return print_json(&session.response());
+            }
+            for order in &session.tree.orders {
+                println!("{} ({})", order.name, order.count);
+                for family in &order.families {
+                    println!("  {} ({})", family.name, family.count);
+                    for genus in &family.genera {
+                        println!("    {} ({})", genus.name, genus.count);
+                        for species in &genus.species {
+                            if species.low_confidence > 0 {
+                                println!(
+                                    "      {} {} ({}, {} low confidence)",
+                                    species.chinese,
+                                    species.latin,
+                                    species.count,
+                                    species.low_confidence
+                                );
+                            } else {
+                                println!(
+                                    "      {} {} ({})",
+                                    species.chinese, species.latin, species.count
+                                );
+                            }
+                            for subspecies in &species.subspecies {
+                                println!("        {} ({})", subspecies.latin, subspecies.count);
+                            }
+                        }
+                        if genus.unidentified > 0 {
+                            println!(
+                                "      {} ({})",
+                                unidentified_name(&genus.name),
+                                genus.unidentified
+                            );
+                        }
+                        print_combinations(&genus.combinations, 6);
+                    }
+                    print_combinations(&family.combinations, 4);
+                }
+                print_combinations(&order.combinations, 2);
+            }
+            print_combinations(&session.tree.combinations, 0);
+            Ok(())
+        }
+        Command::Species { latin, scan: args } => {
+            let session = scan(&cli, args)?;
+            let page = session.species_photos(latin, 0, usize::MAX, PhotoSort::Date)?;
+            if cli.json {
+                return print_json(&page);
+            }
+            for photo in &page.photos {
+                println!(
+                    "{}\t{}\t{}",
+                    photo.taken_at.as_deref().unwrap_or("-"),
+                    photo.confidence,
+                    photo.path
+                );
+            }
+            Ok(())
+        }
+        Command::Search {
+            query,
+            limit,
+            scan: args,
+        } => {
+            let session = scan(&cli, args)?;
+            let hits = session.search(query, *limit);
+            if cli.json {
+                return print_json(&hits);
+            }
+            for hit in &hits {
+                let target = hit.photo.clone().unwrap_or_else(|| {
+                    format!(
+                        "{} {}",
+                        hit.chinese.as_deref().unwrap_or_default(),
+                        hit.latin.as_deref().unwrap_or_default()
+                    )
+                });
+                println!(
+                    "{:?}\t{}\t{}",
+                    hit.matched,
+                    target.trim(),
+                    hit.tree_path.join(" > ")
+                );
+            }
+            Ok(())
+        }
+        Command::Unmatched(args) => {
+            let session = scan(&cli, args)?;
+            if cli.json {
+                return print_json(&session.unmatched);
+            }
+            for path in &session.unmatched {
+                println!("{path}");
+            }
+            Ok(())
+        }
+        Command::Vetoed(args) => {
+            let session = scan(&cli, args)?;
+            if cli.json {
+                return print_json(&session.vetoed);
+            }
+            for vetoed in &session.vetoed {
+                println!("{}\t{}\t{}", vetoed.path, vetoed.latin, vetoed.reason);
+            }
+            Ok(())
+        }
+        Command::Export {
+            format,
+            output,
+            year,
+            only_root,
+            subtree,
+            absolute_links,
+            scan: args,
+        } => {
+            let subtree: Vec<String> = match (subtree, format) {
+                (None, _) => Vec::new(),
+                (Some(path), CliExportFormat::Dwca) => path
+                    .split('/')
+                    .map(|part| part.trim().to_string())
+                    .filter(|part| !part.is_empty())
+                    .collect(),
+                (Some(_), _) => return Err(anyhow!("--subtree only applies to --format dwca")),
+            };
+            let session = scan(&cli, args)?;
+            let filter = ExportFilter {
+                year: *year,
+                root: only_root.clone(),
+            };
+            let count = match format {
+                CliExportFormat::Csv | CliExportFormat::Xlsx | CliExportFormat::Markdown => {
+                    let format = match format {
+                        CliExportFormat::Csv => ExportFormat::Csv,
+                        CliExportFormat::Xlsx => ExportFormat::Xlsx,
+                        _ => ExportFormat::Markdown,
+                    };
+                    let rows = export::life_list(&session, &filter);
+                    export::write_life_list(&rows, format, output)?;
+                    rows.len()
+                }
+                CliExportFormat::Dwca => {
+                    export::write_darwin_core(&session, &subtree, &filter, output)?
+                }
+                CliExportFormat::Html => {
+                    let links = if *absolute_links {
+                        GalleryLinks::Absolute
+                    } else {
+                        GalleryLinks::Relative
+                    };
+                    gallery::write_gallery(&session.tree, &session.roots, output, links)?
+                }
+            };
+            if cli.json {
+                print_json(&count)
+            } else {
+                println!("Exported {count} records to {}", output.display());
+                Ok(())
+            }
+        }
+        Command::Serve {
+            bind,
+            token,
+            allow_rescan,
+            scan: args,
+        } => {
+            let session = scan(&cli, args)?;
+            let token = match token {
+                Some(token) => token.clone(),
+                None => server::generate_token()?,
+            };
+            let config = server::ServerConfig {
+                bind: bind.clone(),
+                token,
+                allow_rescan: *allow_rescan,
+                preview_dir: std::env::temp_dir().join("birdindex2-previews"),
+            };
+            println!("Listening on http://{}", config.bind);
+            println!("Token: {}", config.token);
+            server::serve(&config, session, || scan(&cli, args))
+        }
+        Command::DiffIoc { old, new } => {
+            let diff = ioc::diff(&IocDatabase::load(old)?, &IocDatabase::load(new)?);
+            if cli.json {
+                return print_json(&diff);
+            }
+            for entry in &diff.added {
+                println!(
+                    "+ {} {} [{} / {}]",
+                    entry.latin, entry.chinese, entry.order, entry.family
+                );
+            }
+            for entry in &diff.removed {
+                println!(
+                    "- {} {} [{} / {}]",
+                    entry.latin, entry.chinese, entry.order, entry.family
+                );
+            }
+            for change in &diff.changed {
+                println!("~ {} ({})", change.after.latin, change.fields.join(", "));
+            }
+            println!(
+                "{} added, {} removed, {} changed",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len()
+            );
+            Ok(())
+        }
+        Command::Code { code } => {
+            let db = IocDatabase::load(&cli.ioc)?;
+            let codes = db.alpha_codes();
+            let hits: Vec<AlphaCode> = match code {
+                Some(code) => {
+                    let species = codes.lookup(code);
+                    if species.is_empty() {
+                        return Err(anyhow!("Unknown code: {code}"));
+                    }
+                    vec![alpha_code(&db, &code.to_uppercase(), species)]
+                }
+                None => codes
+                    .ambiguous()
+                    .into_iter()
+                    .map(|(code, species)| alpha_code(&db, code, species))
+                    .collect(),
+            };
+            if cli.json {
+                return print_json(&hits);
+            }
+            for hit in &hits {
+                if hit.species.len() > 1 {
+                    println!("{} is ambiguous:", hit.code);
+                }
+                for entry in &hit.species {
+                    println!(
+                        "{}\t{} {} ({})",
+                        hit.code, entry.latin, entry.chinese, entry.english
+                    );
+                }
+            }
+            Ok(())
+        }
+        Command::Override(command) => {
+            let path = cli
+                .overrides
+                .as_ref()
+                .ok_or_else(|| anyhow!("--overrides is required for override commands"))?;
+            let mut store = OverrideStore::load(path)?;
+            match command {
+                OverrideCommand::Set {
+                    path: photo,
+                    latin,
+                    ignore: _,
+                    by_content,
+                } => {
+                    let action = match latin {
+                        Some(latin) => {
+                            let action = OverrideAction::Species {
+                                latin: latin.clone(),
+                            };
+                            overrides::validate(&action, &IocDatabase::load(&cli.ioc)?.entries)?;
+                            action
+                        }
+                        None => OverrideAction::Ignore,
+                    };
+                    store.set(photo, action, *by_content)?;
+                    store.save(path)
+                }
+                OverrideCommand::Clear { path: photo } => {
+                    if !store.clear(photo) {
+                        return Err(anyhow!("No override for: {}", photo.display()));
+                    }
+                    store.save(path)
+                }
+                OverrideCommand::List => {
+                    if cli.json {
+                        return print_json(&store.list());
+                    }
+                    for photo in store.list() {
+                        let action = match &photo.action {
+                            OverrideAction::Species { latin } => latin.as_str(),
+                            OverrideAction::Ignore => "(ignored)",
+                        };
+                        match &photo.content {
+                            Some(content) => println!("{}\t{action}\t{content}", photo.path),
+                            None => println!("{}\t{action}", photo.path),
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn scan(cli: &Cli, args: &ScanArgs) -> Result<ScanSession> {
+    if !cli.ioc.exists() {
+        return Err(anyhow!("IOC workbook not found: {}", cli.ioc.display()));
+    }
+    let cache = cli
+        .cache
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("birdindex2-scan-cache.json"));
+    let request = ScanRequest {
+        roots: args.roots.clone(),
+        tree: TreeOptions {
+            sort: match args.sort {
+                CliTreeSort::Ioc => TreeSort::Ioc,
+                CliTreeSort::Alphabetical => TreeSort::Alphabetical,
+                CliTreeSort::Count => TreeSort::Count,
+            },
+            full_tree: args.full_tree,
+            subspecies: args.subspecies,
+            min_confidence: args.min_confidence,
+        },
+        matching: MatchOptions {
+            traditional: args.match_traditional,
+            pinyin: args.match_pinyin,
+            pinyin_initials: args.match_pinyin_initials,
+            strip_accents: args.strip_accents,
+            alpha_codes: args.match_codes,
+            english_names: args.match_english,
+            fuzzy_latin: args.match_fuzzy,
+        },
+        templates: args.templates.clone(),
+        exclusions: ExclusionOptions {
+            never_match: args.never_match.clone(),
+            veto_words: args.veto_words.clone(),
+        },
+    };
+    let overrides = match &cli.overrides {
+        Some(path) => OverrideStore::load(path)?,
+        None => OverrideStore::empty(),
+    };
+    let session = scan_and_build(request, &cli.ioc, &cache, &overrides)?;
+    for problem in &session.alias_problems {
+        eprintln!("warning: {}: {problem}", aliases_path(&cli.ioc).display());
+    }
+    for problem in &session.override_problems {
+        eprintln!("warning: override ignored: {problem}");
+    }
+    Ok(session)
+}
+
+fn alpha_code(db: &IocDatabase, code: &str, species: &[usize]) -> AlphaCode {
+    AlphaCode {
+        code: code.to_string(),
+        species: species.iter().map(|&idx| db.entries[idx].clone()).collect(),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_combinations(combinations: &[CombinationNode], indent: usize) {
+    for combination in combinations {
+        println!(
+            "{:indent$}{} {} ({})",
+            "", combination.chinese, combination.name, combination.count
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::{anyhow, Result};
+use birdindex_core::preview::resolve_preview;
+use birdindex_core::search;
+use birdindex_core::session::ScanSession;
+use birdindex_core::types::PhotoSort;
+use serde::Serialize;
+use serde_json::json;
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use subtle::ConstantTimeEq;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+const WORKERS: usize = 8;
+const DEFAULT_PAGE: usize = 200;
+
+type HttpResponse = Response<Box<dyn Read + Send>>;
+
+pub struct ServerConfig {
+    pub bind: String,
+    pub token: String,
+    /// Whether `POST /api/scan` may rescan the roots.
+    pub allow_rescan: bool,
+    pub preview_dir: PathBuf,
+}
+
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::new(500, format!("{err:#}"))
+    }
+}
+
+/// For lookups whose only failure is a missing species, genus or tree node.
+fn not_found(err: anyhow::Error) -> ApiError {
+    ApiError::new(404, format!("{err:#}"))
+}
+
+/// 32 random bytes from the OS, hex encoded.
+pub fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|err| anyhow!("Failed to generate token: {err}"))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+pub fn serve<F>(config: &ServerConfig, session: ScanSession, rescan: F) -> Result<()>
+where
+    F: Fn() -> Result<ScanSession> + Sync,
+{
+    let server = Server::http(&config.bind)
+        .map_err(|err| anyhow!("Failed to bind {}: {err}", config.bind))?;
+    let session = RwLock::new(session);
+
+    std::thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| {
+                while let Ok(request) = server.recv() {
+                    handle(request, config, &session, &rescan);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle<F>(request: Request, config: &ServerConfig, session: &RwLock<ScanSession>, rescan: &F)
+where
+    F: Fn() -> Result<ScanSession>,
+{
+    let response = if *request.method() == Method::Options {
+        Ok(empty(204))
+    } else {
+        route(&request, config, session, rescan)
+    };
+    let response =
+        response.unwrap_or_else(|err| json_response(err.status, &json!({ "error": err.message })));
+    let response = response
+        .with_header(header("Access-Control-Allow-Origin", "*"))
+        .with_header(header(
+            "Access-Control-Allow-Headers",
+            "Authorization, Range",
+        ))
+        .with_header(header(
+            "Access-Control-Expose-Headers",
+            "Content-Range, Content-Length, Accept-Ranges",
+        ));
+    if let Err(err) = request.respond(response) {
+        eprintln!("warning: failed to send response: {err}");
+    }
+}
+
+fn route<F>(
+    request: &Request,
+    config: &ServerConfig,
+    session: &RwLock<ScanSession>,
+    rescan: &F,
+) -> Result<HttpResponse, ApiError>
+where
+    F: Fn() -> Result<ScanSession>,
+{
+    let (path, query) = split_url(request.url());
+    if !authorized(request.headers(), &query, &config.token) {
+        return Err(ApiError::new(401, "Missing or invalid token"));
+    }
+
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let method = request.method();
+    let read = || {
+        session
+            .read()
+            .map_err(|_| ApiError::new(500, "Session lock poisoned"))
+    };
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["api", "scan"]) => Ok(json_response(200, &read()?.response())),
+        (Method::Post, ["api", "scan"]) => {
+            if !config.allow_rescan {
+                return Err(ApiError::new(
+                    403,
+                    "Rescanning is disabled; start the server with --allow-rescan",
+                ));
+            }
+            let fresh = rescan().map_err(|err| ApiError::new(500, format!("{err:#}")))?;
+            let response = json_response(200, &fresh.response());
+            *session
+                .write()
+                .map_err(|_| ApiError::new(500, "Session lock poisoned"))? = fresh;
+            Ok(response)
+        }
+        (Method::Get, ["api", "stats"]) => Ok(json_response(200, &read()?.stats)),
+        (Method::Get, ["api", "tree", tree_path @ ..]) => {
+            let tree_path: Vec<String> = tree_path.iter().map(|part| part.to_string()).collect();
+            let children = read()?.node_children(&tree_path).map_err(not_found)?;
+            Ok(json_response(200, &children))
+        }
+        (Method::Get, ["api", "species", latin]) => {
+            let offset = query_usize(&query, "offset")?.unwrap_or(0);
+            let limit = query_usize(&query, "limit")?.unwrap_or(DEFAULT_PAGE);
+            let sort = match query_value(&query, "sort") {
+                None | Some("name") => PhotoSort::Name,
+                Some("path") => PhotoSort::Path,
+                Some("date") => PhotoSort::Date,
+                Some(other) => return Err(ApiError::new(400, format!("Unknown sort: {other}"))),
+            };
+            let page = read()?
+                .species_photos(latin, offset, limit, sort)
+                .map_err(not_found)?;
+            Ok(json_response(200, &page))
+        }
+        (Method::Get, ["api", "search"]) => {
+            let q = query_value(&query, "q")
+                .ok_or_else(|| ApiError::new(400, "Missing query parameter: q"))?;
+            let limit = query_usize(&query, "limit")?.unwrap_or(search::DEFAULT_LIMIT);
+            Ok(json_response(200, &read()?.search(q, limit)))
+        }
+        (Method::Get, ["api", "unmatched"]) => Ok(json_response(200, &read()?.unmatched)),
+        (Method::Get, ["api", "vetoed"]) => Ok(json_response(200, &read()?.vetoed)),
+        (Method::Get, ["api", kind @ ("original" | "thumbnail")]) => {
+            let photo = query_value(&query, "path")
+                .ok_or_else(|| ApiError::new(400, "Missing query parameter: path"))?;
+            if !read()?.contains_photo(photo) {
+                return Err(ApiError::new(404, format!("Photo not indexed: {photo}")));
+            }
+            let file = if *kind == "thumbnail" {
+                let preview = resolve_preview(photo, &config.preview_dir);
+                PathBuf::from(preview.path)
+            } else {
+                PathBuf::from(photo)
+            };
+            file_response(&file, range_header(request))
+        }
+        (Method::Get | Method::Post, _) => Err(ApiError::new(404, format!("Not found: {path}"))),
+        _ => Err(ApiError::new(405, "Method not allowed")),
+    }
+}
+
+fn authorized(headers: &[Header], query: &[(String, String)], token: &str) -> bool {
+    let bearer = headers
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "));
+    bearer
+        .or_else(|| query_value(query, "token"))
+        .is_some_and(|sent| sent.as_bytes().ct_eq(token.as_bytes()).into())
+}
+
+fn range_header(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Range"))
+        .map(|header| header.value.as_str().to_string())
+}
+
+fn file_response(path: &Path, range: Option<String>) -> Result<HttpResponse, ApiError> {
+    let mut file = File::open(path).map_err(|err| {
+        let status = if err.kind() == ErrorKind::NotFound {
+            404
+        } else {
+            500
+        };
+        ApiError::new(
+            status,
+            format!("Failed to open file: {}: {err}", path.display()),
+        )
+    })?;
+    let len = file
+        .metadata()
+        .map_err(|err| ApiError::new(500, err.to_string()))?
+        .len();
+
+    let (status, start, end) = match range {
+        None => (200, 0, len.saturating_sub(1)),
+        Some(value) => match byte_range(&value, len) {
+            Some((start, end)) => (206, start, end),
+            None => {
+                return Ok(
+                    empty(416).with_header(header("Content-Range", &format!("bytes */{len}")))
+                )
+            }
+        },
+    };
+    let body_len = if len == 0 { 0 } else { end - start + 1 };
+    file.seek(SeekFrom::Start(start))
+        .map_err(|err| ApiError::new(500, err.to_string()))?;
+
+    let mut response = Response::new(
+        StatusCode(status),
+        vec![
+            header("Content-Type", content_type(path)),
+            header("Accept-Ranges", "bytes"),
+        ],
+        Box::new(file.take(body_len)) as Box<dyn Read + Send>,
+        Some(body_len as usize),
+        None,
+    );
+    if status == 206 {
+        response = response.with_header(header(
+            "Content-Range",
+            &format!("bytes {start}-{end}/{len}"),
+        ));
+    }
+    Ok(response)
+}
+
+/// Parses a single `bytes=` range into inclusive offsets, or `None` when unsatisfiable.
+fn byte_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "heic" => "image/heic",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, value: &T) -> HttpResponse {
+    let body = serde_json::to_vec(value).unwrap_or_default();
+    let len = body.len();
+    Response::new(
+        StatusCode(status),
+        vec![header("Content-Type", "application/json; charset=utf-8")],
+        Box::new(Cursor::new(body)) as Box<dyn Read + Send>,
+        Some(len),
+        None,
+    )
+}
+
+fn empty(status: u16) -> HttpResponse {
+    Response::empty(StatusCode(status)).boxed()
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+fn split_url(url: &str) -> (&str, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            // Form encoding: `+` is a space in the query, `%2B` a plus.
+            let decode = |part: &str| percent_decode(&part.replace('+', " "));
+            (decode(key), decode(value))
+        })
+        .collect();
+    (path, pairs)
+}
+
+fn query_value<'a>(query: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    query
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.as_str())
+}
+
+fn query_usize(query: &[(String, String)], key: &str) -> Result<Option<usize>, ApiError> {
+    query_value(query, key)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| ApiError::new(400, format!("Invalid {key}: {value}")))
+        })
+        .transpose()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                idx += 3;
+            }
+            None => {
+                out.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{authorized, byte_range, header, split_url};
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(byte_range("bytes=0-9", 100), Some((0, 9)));
+        assert_eq!(byte_range("bytes=90-", 100), Some((90, 99)));
+        assert_eq!(byte_range("bytes=95-200", 100), Some((95, 99)));
+        assert_eq!(byte_range("bytes=-10", 100), Some((90, 99)));
+        assert_eq!(byte_range("bytes=-200", 100), Some((0, 99)));
+        assert_eq!(byte_range("bytes=100-", 100), None);
+        assert_eq!(byte_range("bytes=-0", 100), None);
+        assert_eq!(byte_range("bytes=5-2", 100), None);
+        assert_eq!(byte_range("bytes=0-1,3-4", 100), None);
+        assert_eq!(byte_range("items=0-9", 100), None);
+        assert_eq!(byte_range("bytes=0-9", 0), None);
+    }
+
+    #[test]
+    fn decodes_query_values() {
+        let (path, query) = split_url("/api/search?q=light+vented&x=a%2Bb&cn=%E7%99%BD%E5%A4%B4");
+        assert_eq!(path, "/api/search");
+        assert_eq!(
+            query,
+            [
+                ("q".to_string(), "light vented".to_string()),
+                ("x".to_string(), "a+b".to_string()),
+                ("cn".to_string(), "白头".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_token() {
+        let token = "0123abcd";
+        let query = |sent: &str| vec![("token".to_string(), sent.to_string())];
+        let bearer = |sent: &str| vec![header("Authorization", &format!("Bearer {sent}"))];
+
+        assert!(authorized(&bearer(token), &[], token));
+        assert!(authorized(&[], &query(token), token));
+        assert!(!authorized(&[], &[], token));
+        assert!(!authorized(&bearer("0123abc"), &[], token));
+        assert!(!authorized(&[], &query("0123abcde"), token));
+        assert!(!authorized(&[header("Authorization", token)], &[], token));
+    }
+}
@@ -0,0 +1,90 @@
+//! The checklist shared by the benchmarks.
+
+use birdindex_core::types::IocEntry;
+
+const IOC: &str = include_str!("../../tests/data/ioc_synthetic.tsv");
+
+/// About the size of the IOC list.
+const SPECIES: usize = 11_000;
+/// About the number of other language columns in the multilingual workbook.
+const OTHER_LANGUAGES: usize = 20;
+
+const SYLLABLES: [&str; 20] = [
+    "ca", "lo", "mi", "ra", "te", "vu", "no", "pe", "si", "du", "ga", "ri", "xo", "be", "fa", "hu",
+    "ke", "ly", "ma", "to",
+];
+
+const CHARACTERS: [char; 40] = [
+    '鹎', '鹡', '鸰', '雀', '鹭', '翠', '鸟', '柳', '莺', '鸭', '鹰', '隼', '鸮', '鸠', '鸦', '鹀',
+    '鹨', '鸫', '鹟', '鹛', '鹪', '鹩', '雉', '鹑', '鹤', '鹳', '鹈', '鹕', '鸥', '燕', '鹊', '鸲',
+    '鹂', '鹃', '啄', '木', '山', '林', '灰', '黑',
+];
+
+/// The synthetic test checklist padded with made-up species, about eight per
+/// genus, each with a name per other language.
+pub fn checklist() -> Vec<IocEntry> {
+    let mut entries: Vec<IocEntry> = IOC
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let cells: Vec<&str> = line.split('\t').collect();
+            IocEntry {
+                seq: 0,
+                order: cells[0].to_string(),
+                family: cells[1].to_string(),
+                latin: cells[2].to_string(),
+                chinese: cells[4].to_string(),
+                english: cells[3].to_string(),
+                subspecies: Vec::new(),
+                other_names: Vec::new(),
+            }
+        })
+        .collect();
+    for idx in entries.len()..SPECIES {
+        let genus = idx / 8;
+        let genus_name = format!(
+            "{}{}{}a",
+            SYLLABLES[genus % 20],
+            SYLLABLES[genus / 20 % 20],
+            SYLLABLES[genus / 400 % 20]
+        );
+        let latin = format!(
+            "{}{} {}{}us",
+            genus_name[..1].to_uppercase(),
+            &genus_name[1..],
+            SYLLABLES[idx % 20],
+            SYLLABLES[idx / 20 % 20]
+        );
+        let chinese: String = [idx % 40, idx / 40 % 40, idx / 1600 % 40]
+            .into_iter()
+            .map(|digit| CHARACTERS[digit])
+            .chain(['鸟'])
+            .collect();
+        let other_names = (0..OTHER_LANGUAGES)
+            .map(|language| {
+                format!(
+                    "{}{} {}{}{}",
+                    SYLLABLES[(idx + language) % 20],
+                    SYLLABLES[(idx / 20 + language) % 20],
+                    SYLLABLES[language],
+                    SYLLABLES[(genus + language) % 20],
+                    SYLLABLES[idx / 400 % 20]
+                )
+            })
+            .collect();
+        entries.push(IocEntry {
+            seq: 0,
+            order: format!("Order{}", genus / 200),
+            family: format!("Family{}", genus / 20),
+            latin,
+            chinese,
+            english: format!("Bird {idx}"),
+            subspecies: Vec::new(),
+            other_names,
+        });
+    }
+    for (seq, entry) in entries.iter_mut().enumerate() {
+        entry.seq = seq;
+    }
+    entries
+}
@@ -0,0 +1,127 @@
+//! Matching and scanning throughput on a generated 100k-photo library, against
+//! the "100,000 photos within about a minute" target.
+//!
+//! The photo tree is written once to the temp directory and reused by later
+//! runs.
+
+mod common;
+
+use birdindex_core::cache::CacheIndex;
+use birdindex_core::matcher::NameMatcher;
+use birdindex_core::overrides::OverrideStore;
+use birdindex_core::scanner::scan_paths;
+use birdindex_core::template::FileTemplates;
+use birdindex_core::types::{IocEntry, MatchOptions};
+use birdindex_core::util::fnv1a;
+use common::checklist;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const PHOTOS: usize = 100_000;
+const PHOTOS_PER_DIR: usize = 1_000;
+
+/// File names in the styles of the test corpus; one in five names no bird.
+fn file_names(entries: &[IocEntry]) -> Vec<String> {
+    (0..PHOTOS)
+        .map(|n| {
+            let entry = &entries[n * 7919 % entries.len()];
+            match n % 5 {
+                0 => format!("{}_{n:05}.jpg", entry.latin.replace(' ', "_")),
+                1 => format!("{}-{n}.NEF", entry.latin.replace(' ', "")),
+                2 => format!("{} {n}.jpg", entry.chinese),
+                3 => format!("IMG_{n:05}.jpg"),
+                _ => format!("2024-05-01_{}_DSC{n}.ARW", entry.latin.replace(' ', "-")),
+            }
+        })
+        .collect()
+}
+
+/// Writes the file names as empty files, a thousand per folder. The folder
+/// is named after a hash of the names, so changed names get a fresh one.
+fn photo_tree(file_names: &[String]) -> PathBuf {
+    let hash = fnv1a(&file_names.join("\n"));
+    let root = std::env::temp_dir().join(format!("birdindex-bench-{PHOTOS}-{hash:016x}"));
+    let done = root.join(".complete");
+    if done.exists() {
+        return root;
+    }
+    for (idx, file_name) in file_names.iter().enumerate() {
+        let dir = root.join(format!("{:03}", idx / PHOTOS_PER_DIR));
+        if idx % PHOTOS_PER_DIR == 0 {
+            fs::create_dir_all(&dir).expect("create bench folder");
+        }
+        fs::write(dir.join(file_name), b"").expect("write bench photo");
+    }
+    fs::write(&done, b"").expect("write bench marker");
+    root
+}
+
+fn latin_index(entries: &[IocEntry]) -> HashMap<String, usize> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| (entry.latin.to_lowercase(), idx))
+        .collect()
+}
+
+fn match_name(c: &mut Criterion) {
+    let entries = checklist();
+    let file_names = file_names(&entries);
+    let matcher = NameMatcher::new(&entries, MatchOptions::default());
+    let matched = |file_names: &[String]| {
+        file_names
+            .iter()
+            .filter(|name| matcher.match_name(name).is_some())
+            .count()
+    };
+    assert_eq!(matched(&file_names), PHOTOS / 5 * 4);
+
+    let mut group = c.benchmark_group("match_name");
+    group.throughput(Throughput::Elements(PHOTOS as u64));
+    group.sample_size(10);
+    group.bench_function("100k", |b| b.iter(|| matched(&file_names)));
+    group.finish();
+}
+
+fn scan(c: &mut Criterion) {
+    let entries = checklist();
+    let latin_index = latin_index(&entries);
+    let root = photo_tree(&file_names(&entries));
+    let roots = vec![root.to_string_lossy().to_string()];
+    let matcher = NameMatcher::new(&entries, MatchOptions::default());
+    let overrides = OverrideStore::empty();
+    let templates = FileTemplates::empty();
+    let run = |cache: &CacheIndex| {
+        scan_paths(
+            &roots,
+            &entries,
+            &latin_index,
+            &matcher,
+            cache,
+            &overrides,
+            &templates,
+        )
+    };
+    let empty = CacheIndex::empty();
+    let warm = CacheIndex {
+        entries: run(&empty)
+            .cache_entries
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect(),
+    };
+
+    let mut group = c.benchmark_group("scan_paths");
+    group.throughput(Throughput::Elements(PHOTOS as u64));
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(30));
+    group.bench_function("100k", |b| b.iter(|| run(&empty).stats.matched_files));
+    group.bench_function("100k_cached", |b| b.iter(|| run(&warm).stats.matched_files));
+    group.finish();
+}
+
+criterion_group!(benches, match_name, scan);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! Search latency over a 500k-photo index, against the "answers in
+//! milliseconds at 500,000 photos" target.
+
+mod common;
+
+use birdindex_core::search::DEFAULT_LIMIT;
+use birdindex_core::session::ScanSession;
+use birdindex_core::tree::build_tree;
+use birdindex_core::types::{
+    DateSource, Identification, MatchSource, MatchedPhoto, ScanStats, TreeOptions,
+};
+use common::checklist;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const PHOTOS: usize = 500_000;
+const PHOTOS_PER_DIR: usize = 1_000;
+
+/// A session over the padded checklist in which four in five photos matched.
+fn session() -> ScanSession {
+    let entries = checklist();
+    let mut matches = Vec::new();
+    let mut unmatched = Vec::new();
+    for n in 0..PHOTOS {
+        let dir = format!("/photos/{:03}", n / PHOTOS_PER_DIR);
+        if n % 5 == 4 {
+            unmatched.push(format!("{dir}/IMG_{n:06}.jpg"));
+            continue;
+        }
+        let species_idx = n * 7919 % entries.len();
+        let file_name = format!(
+            "{}_{n:06}.jpg",
+            entries[species_idx].latin.replace(' ', "_")
+        );
+        matches.push(MatchedPhoto {
+            path: format!("{dir}/{file_name}"),
+            file_name,
+            species_idx,
+            identification: Identification::Species,
+            subspecies: None,
+            taken_at: None,
+            date_source: DateSource::Mtime,
+            latitude: None,
+            longitude: None,
+            location: None,
+            sequence: None,
+            source: MatchSource::Latin,
+            confidence: 100,
+        });
+    }
+
+    let tree = build_tree(&entries, &matches, TreeOptions::default());
+    let stats = ScanStats {
+        total_files: PHOTOS,
+        matched_files: matches.len(),
+        unmatched_files: unmatched.len(),
+        ignored_files: 0,
+        vetoed_files: 0,
+    };
+    ScanSession::new(tree, stats, vec!["/photos".to_string()], unmatched, entries)
+}
+
+fn search(c: &mut Criterion) {
+    let session = session();
+    let mut group = c.benchmark_group("search_500k");
+    for (name, query) in [
+        ("latin", "Pycnonotus sinensis"),
+        ("prefix", "pycno"),
+        ("fuzzy", "pycnonotis"),
+        ("chinese", "白头"),
+        ("other_language", "mara caca"),
+        ("photo", "IMG_049999"),
+        ("no_hits", "zzzzqqq"),
+    ] {
+        assert!(
+            name == "no_hits" || !session.search(query, DEFAULT_LIMIT).is_empty(),
+            "no hits for {query}"
+        );
+        group.bench_function(name, |b| b.iter(|| session.search(query, DEFAULT_LIMIT)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
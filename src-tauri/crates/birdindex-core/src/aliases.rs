@@ -0,0 +1,150 @@
+use crate::tree::genus_name;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the alias dictionary, looked up next to the IOC workbook.
+pub const ALIASES_FILE: &str = "aliases.csv";
+
+/// User-defined names for IOC species, such as house abbreviations (`LVB`),
+/// short Chinese names (`白头`) or banding codes, and for genera, such as
+/// Chinese group names (`柳莺`). Read from a CSV file with one
+/// `alias,Latin name` pair per line; `#` starts a comment line.
+#[derive(Debug, Default, Clone)]
+pub struct AliasDictionary {
+    /// Each species alias with the index of its IOC entry, in file order.
+    pub aliases: Vec<(String, usize)>,
+    /// Each genus alias with the index of the genus's first IOC entry, in
+    /// file order.
+    pub genera: Vec<(String, usize)>,
+    /// Lines that were skipped, e.g. because the Latin name is not in the
+    /// checklist.
+    pub problems: Vec<String>,
+    fingerprint: String,
+}
+
+impl AliasDictionary {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Loads the dictionary, or an empty one when the file does not exist.
+    pub fn load<P: AsRef<Path>>(path: P, latin_index: &HashMap<String, usize>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::empty());
+        }
+
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read alias file: {}", path.display()))?;
+        Ok(Self::parse(&text, latin_index))
+    }
+
+    /// Parses CSV text, resolving targets through the lowercase Latin index.
+    pub fn parse(text: &str, latin_index: &HashMap<String, usize>) -> Self {
+        let mut aliases = Vec::new();
+        let mut genera = Vec::new();
+        let mut seen: HashMap<String, (bool, usize)> = HashMap::new();
+        let mut problems = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((alias, latin)) = line.rsplit_once(',') else {
+                problems.push(format!("line {number}: expected `alias,Latin name`"));
+                continue;
+            };
+            let alias = unquote(alias);
+            let latin = unquote(latin);
+            if number == 1 && alias.eq_ignore_ascii_case("alias") {
+                continue;
+            }
+            if alias.is_empty() {
+                problems.push(format!("line {number}: empty alias"));
+                continue;
+            }
+
+            let key = latin
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase();
+            let target = match latin_index.get(&key) {
+                Some(&idx) => (false, idx),
+                None if !key.contains(' ') => match first_of_genus(&key, latin_index) {
+                    Some(idx) => (true, idx),
+                    None => {
+                        problems.push(format!("line {number}: unknown genus `{latin}`"));
+                        continue;
+                    }
+                },
+                None => {
+                    problems.push(format!("line {number}: unknown species `{latin}`"));
+                    continue;
+                }
+            };
+            match seen.get(&alias.to_lowercase()) {
+                Some(&existing) if existing != target => {
+                    problems.push(format!(
+                        "line {number}: `{alias}` is already an alias of another species or genus"
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(alias.to_lowercase(), target);
+                    match target {
+                        (true, idx) => genera.push((alias.to_string(), idx)),
+                        (false, idx) => aliases.push((alias.to_string(), idx)),
+                    }
+                }
+            }
+        }
+
+        let fingerprint = Sha256::digest(text.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        Self {
+            aliases,
+            genera,
+            problems,
+            fingerprint,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.genera.is_empty()
+    }
+
+    /// Identifies the dictionary contents, for keying the scan cache.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
+
+/// The alias file belonging to an IOC workbook.
+pub fn aliases_path(ioc_path: &Path) -> PathBuf {
+    ioc_path.with_file_name(ALIASES_FILE)
+}
+
+/// The first IOC entry of a lowercase genus name.
+fn first_of_genus(genus: &str, latin_index: &HashMap<String, usize>) -> Option<usize> {
+    latin_index
+        .iter()
+        .filter(|(latin, _)| genus_name(latin) == genus)
+        .map(|(_, &idx)| idx)
+        .min()
+}
+
+fn unquote(field: &str) -> &str {
+    let field = field.trim();
+    field
+        .strip_prefix('"')
+        .and_then(|field| field.strip_suffix('"'))
+        .unwrap_or(field)
+        .trim()
+}
@@ -1,4 +1,4 @@
-use crate::core::types::{CacheEntry, CacheFile};
+use crate::types::{CacheEntry, CacheFile};
 use anyhow::{Context, Result};
 use serde_json::json;
 use std::collections::HashMap;
@@ -6,6 +6,8 @@ use std::fs;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+const CACHE_VERSION: u32 = 14;
+
 pub struct CacheIndex {
     pub entries: HashMap<String, CacheEntry>,
 }
@@ -33,7 +35,7 @@ pub fn load_cache<P: AsRef<Path>>(path: P, ioc_fingerprint: &str) -> Result<Cach
     let cache: CacheFile = serde_json::from_str(&data)
         .with_context(|| format!("Failed to parse cache file: {}", path.display()))?;
 
-    if cache.ioc_fingerprint != ioc_fingerprint {
+    if cache.version != CACHE_VERSION || cache.ioc_fingerprint != ioc_fingerprint {
         return Ok(CacheIndex::empty());
     }
 
@@ -57,7 +59,7 @@ pub fn save_cache<P: AsRef<Path>>(
     }
 
     let cache = CacheFile {
-        version: 1,
+        version: CACHE_VERSION,
         ioc_fingerprint: ioc_fingerprint.to_string(),
         entries,
     };
@@ -69,6 +71,7 @@ pub fn save_cache<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Identifies a checklist file by size and mtime.
 pub fn fingerprint<P: AsRef<Path>>(path: P) -> Result<String> {
     let meta = fs::metadata(&path)
         .with_context(|| format!("Failed to read metadata: {}", path.as_ref().display()))?;
@@ -0,0 +1,54 @@
+#[cfg(feature = "chinese")]
+use pinyin::ToPinyin;
+#[cfg(feature = "chinese")]
+use zhconv::{zhconv, Variant};
+
+/// Alternate spellings of a Simplified Chinese name, each lowercase and empty
+/// when unavailable.
+#[derive(Debug, Clone, Default)]
+pub struct ChineseForms {
+    /// Traditional characters, e.g. 白頭鵯; empty when identical to the input.
+    pub traditional: String,
+    /// Toneless pinyin without separators, e.g. baitoubei.
+    pub pinyin: String,
+    /// First letters of each syllable, e.g. btb.
+    pub initials: String,
+}
+
+#[cfg(feature = "chinese")]
+pub fn chinese_forms(chinese: &str) -> ChineseForms {
+    let chinese = chinese.trim();
+    if chinese.is_empty() {
+        return ChineseForms::default();
+    }
+
+    let traditional = zhconv(chinese, Variant::ZhHant);
+    // Names with non-Han characters get no pinyin rather than a partial one.
+    let syllables: Option<Vec<_>> = chinese.to_pinyin().collect();
+    let (pinyin, initials) = syllables
+        .map(|syllables| {
+            (
+                syllables.iter().map(|syllable| syllable.plain()).collect(),
+                syllables
+                    .iter()
+                    .map(|syllable| syllable.first_letter())
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+
+    ChineseForms {
+        traditional: if traditional == chinese {
+            String::new()
+        } else {
+            traditional.to_lowercase()
+        },
+        pinyin,
+        initials,
+    }
+}
+
+#[cfg(not(feature = "chinese"))]
+pub fn chinese_forms(_chinese: &str) -> ChineseForms {
+    ChineseForms::default()
+}
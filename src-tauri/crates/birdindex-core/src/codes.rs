@@ -0,0 +1,159 @@
+use crate::normalize::normalize;
+use crate::types::IocEntry;
+use std::collections::{HashMap, HashSet};
+
+/// Letters taken from each word of an English name, best first. Names of more
+/// than four words use the first three words and the last.
+const ENGLISH_SPLITS: [&[&[usize]]; 4] = [
+    &[&[4]],
+    &[&[2, 2], &[1, 3], &[3, 1]],
+    &[&[1, 1, 2], &[1, 2, 1], &[2, 1, 1]],
+    &[&[1, 1, 1, 1]],
+];
+
+/// Letters taken from the genus and the epithet of a Latin name, best first.
+const LATIN_SPLITS: [&[usize]; 3] = [&[3, 3], &[2, 4], &[4, 2]];
+
+/// Four-letter codes from English names and six-letter codes from Latin
+/// names, as used by bird banders (`LVBU`, `PYCSIN`). When several species
+/// share a code, each gets the first alternative split no other species uses
+/// and the shared code stays ambiguous.
+#[derive(Debug, Default)]
+pub struct AlphaCodes {
+    codes: HashMap<String, Vec<usize>>,
+}
+
+impl AlphaCodes {
+    pub fn generate(entries: &[IocEntry]) -> Self {
+        let mut codes = HashMap::new();
+        let english: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| english_candidates(&entry.english))
+            .collect();
+        let latin: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| latin_candidates(&entry.latin))
+            .collect();
+        assign(&english, &mut codes);
+        assign(&latin, &mut codes);
+        Self { codes }
+    }
+
+    /// Entries with this code, case-insensitively; more than one when it is
+    /// ambiguous.
+    pub fn lookup(&self, code: &str) -> &[usize] {
+        self.codes
+            .get(&code.trim().to_uppercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Codes that name exactly one species.
+    pub fn unique(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.codes
+            .iter()
+            .filter(|(_, entries)| entries.len() == 1)
+            .map(|(code, entries)| (code.as_str(), entries[0]))
+    }
+
+    /// Codes shared by several species, sorted.
+    pub fn ambiguous(&self) -> Vec<(&str, &[usize])> {
+        let mut codes: Vec<(&str, &[usize])> = self
+            .codes
+            .iter()
+            .filter(|(_, entries)| entries.len() > 1)
+            .map(|(code, entries)| (code.as_str(), entries.as_slice()))
+            .collect();
+        codes.sort();
+        codes
+    }
+}
+
+fn assign(candidates: &[Vec<String>], codes: &mut HashMap<String, Vec<usize>>) {
+    let mut primaries: HashMap<&str, usize> = HashMap::new();
+    for first in candidates.iter().filter_map(|list| list.first()) {
+        *primaries.entry(first.as_str()).or_insert(0) += 1;
+    }
+    let mut taken: HashSet<&str> = primaries.keys().copied().collect();
+
+    for (idx, list) in candidates.iter().enumerate() {
+        let Some(primary) = list.first() else {
+            continue;
+        };
+        codes.entry(primary.clone()).or_default().push(idx);
+        if primaries[primary.as_str()] == 1 {
+            continue;
+        }
+        if let Some(alternative) = list[1..].iter().find(|code| !taken.contains(code.as_str())) {
+            taken.insert(alternative.as_str());
+            codes.insert(alternative.clone(), vec![idx]);
+        }
+    }
+}
+
+fn english_candidates(english: &str) -> Vec<String> {
+    let mut words: Vec<String> = english
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .map(letters)
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.len() > 4 {
+        words.drain(3..words.len() - 1);
+    }
+    let Some(splits) = words.len().checked_sub(1).map(|n| ENGLISH_SPLITS[n]) else {
+        return Vec::new();
+    };
+
+    let mut candidates = take_letters(&words, splits);
+    // A one-word name falls back to its first three letters plus a later one.
+    if let [word] = words.as_slice() {
+        for letter in word.chars().skip(4) {
+            candidates.push(format!("{}{letter}", &word[..3]));
+        }
+    }
+    dedup(candidates)
+}
+
+fn latin_candidates(latin: &str) -> Vec<String> {
+    let words: Vec<String> = latin.split_whitespace().take(2).map(letters).collect();
+    if words.len() < 2 {
+        return Vec::new();
+    }
+    dedup(take_letters(&words, &LATIN_SPLITS))
+}
+
+fn take_letters(words: &[String], splits: &[&[usize]]) -> Vec<String> {
+    splits
+        .iter()
+        .filter(|split| {
+            words
+                .iter()
+                .zip(split.iter())
+                .all(|(word, &n)| word.len() >= n)
+        })
+        .map(|split| {
+            words
+                .iter()
+                .zip(split.iter())
+                .map(|(word, &n)| &word[..n])
+                .collect()
+        })
+        .collect()
+}
+
+/// Uppercase ASCII letters of a word, accents stripped.
+fn letters(word: &str) -> String {
+    normalize(word, true)
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn dedup(candidates: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|code| seen.insert(code.clone()))
+        .collect()
+}
@@ -0,0 +1,422 @@
+use crate::metadata::{date_part, year_part};
+use crate::session::ScanSession;
+use crate::types::{DateSource, ExportFilter, ExportFormat, LifeListRow, PhotoItem};
+use crate::util::fnv1a;
+use anyhow::{Context, Result};
+#[cfg(feature = "xlsx")]
+use rust_xlsxwriter::Workbook;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const LIFE_LIST_HEADERS: [&str; 8] = [
+    "No.",
+    "Latin",
+    "Chinese",
+    "English",
+    "Order",
+    "Family",
+    "Photos",
+    "First photographed",
+];
+
+/// Life list columns written as numbers in XLSX: "No." and "Photos".
+const LIFE_LIST_NUMERIC: [usize; 2] = [0, 6];
+
+const DWC_TERMS: [&str; 17] = [
+    "occurrenceID",
+    "basisOfRecord",
+    "scientificName",
+    "taxonRank",
+    "kingdom",
+    "class",
+    "order",
+    "family",
+    "genus",
+    "higherClassification",
+    "vernacularName",
+    "eventDate",
+    "decimalLatitude",
+    "decimalLongitude",
+    "geodeticDatum",
+    "locality",
+    "associatedMedia",
+];
+
+const DWC_NAMESPACE: &str = "http://rs.tdwg.org/dwc/terms/";
+
+pub fn photo_matches(filter: &ExportFilter, photo: &PhotoItem) -> bool {
+    if let Some(root) = &filter.root {
+        if !Path::new(&photo.path).starts_with(root) {
+            return false;
+        }
+    }
+    if let Some(year) = filter.year {
+        let taken_year = photo.taken_at.as_deref().and_then(year_part);
+        if taken_year != Some(year) {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn life_list(session: &ScanSession, filter: &ExportFilter) -> Vec<LifeListRow> {
+    let mut rows = Vec::new();
+    for (_, _, _, species) in session.species_under(&[]).unwrap_or_default() {
+        let photos: Vec<&PhotoItem> = species
+            .photos
+            .iter()
+            .filter(|photo| photo_matches(filter, photo))
+            .collect();
+        if photos.is_empty() {
+            continue;
+        }
+        let entry = match session.entry(&species.latin) {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        rows.push(LifeListRow {
+            seq: entry.seq,
+            latin: entry.latin.clone(),
+            chinese: entry.chinese.clone(),
+            english: entry.english.clone(),
+            order: entry.order.clone(),
+            family: entry.family.clone(),
+            count: photos.len(),
+            first_taken: photos
+                .iter()
+                .filter_map(|photo| photo.taken_at.clone())
+                .min(),
+        });
+    }
+
+    rows.sort_by_key(|row| row.seq);
+    rows
+}
+
+pub fn write_life_list<P: AsRef<Path>>(
+    rows: &[LifeListRow],
+    format: ExportFormat,
+    path: P,
+) -> Result<()> {
+    let path = path.as_ref();
+    let records: Vec<[String; 8]> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            [
+                (idx + 1).to_string(),
+                row.latin.clone(),
+                row.chinese.clone(),
+                row.english.clone(),
+                row.order.clone(),
+                row.family.clone(),
+                row.count.to_string(),
+                row.first_taken
+                    .as_deref()
+                    .map(|taken| date_part(taken).to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    match format {
+        ExportFormat::Csv => write_text(path, &to_csv(&LIFE_LIST_HEADERS, &records)),
+        ExportFormat::Markdown => write_text(path, &to_markdown(&LIFE_LIST_HEADERS, &records)),
+        ExportFormat::Xlsx => write_xlsx(
+            path,
+            "Life List",
+            &LIFE_LIST_HEADERS,
+            &LIFE_LIST_NUMERIC,
+            &records,
+        ),
+    }
+}
+
+fn write_text(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create export dir: {}", parent.display()))?;
+    }
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write export file: {}", path.display()))
+}
+
+fn to_csv<const N: usize>(headers: &[&str; N], records: &[[String; N]]) -> String {
+    let mut out = String::from("\u{feff}");
+    let header: Vec<String> = headers.iter().map(|value| csv_field(value)).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+    for record in records {
+        let line: Vec<String> = record.iter().map(|value| csv_field(value)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_markdown<const N: usize>(headers: &[&str; N], records: &[[String; N]]) -> String {
+    let mut out = format!("| {} |\n", headers.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(N)));
+    for record in records {
+        let cells: Vec<String> = record.iter().map(|value| markdown_cell(value)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Escapes pipes and keeps line breaks from ending the table row.
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+/// Writes the `numeric` columns as numbers and everything else as text.
+#[cfg(feature = "xlsx")]
+fn write_xlsx<const N: usize>(
+    path: &Path,
+    sheet: &str,
+    headers: &[&str; N],
+    numeric: &[usize],
+    records: &[[String; N]],
+) -> Result<()> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet)?;
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string(0, col as u16, *header)?;
+    }
+    for (row, record) in records.iter().enumerate() {
+        for (col, value) in record.iter().enumerate() {
+            let (row, col) = (row as u32 + 1, col as u16);
+            match value.parse::<f64>() {
+                Ok(number) if numeric.contains(&usize::from(col)) => {
+                    worksheet.write_number(row, col, number)?;
+                }
+                _ => {
+                    worksheet.write_string(row, col, value)?;
+                }
+            }
+        }
+    }
+    workbook
+        .save(path)
+        .with_context(|| format!("Failed to write export file: {}", path.display()))
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn write_xlsx<const N: usize>(
+    _path: &Path,
+    _sheet: &str,
+    _headers: &[&str; N],
+    _numeric: &[usize],
+    _records: &[[String; N]],
+) -> Result<()> {
+    anyhow::bail!("XLSX export requires the `xlsx` feature")
+}
+
+pub fn write_darwin_core<P: AsRef<Path>>(
+    session: &ScanSession,
+    subtree: &[String],
+    filter: &ExportFilter,
+    path: P,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let mut occurrences = String::new();
+    occurrences.push_str(&DWC_TERMS.join("\t"));
+    occurrences.push('\n');
+
+    let mut count = 0usize;
+    for (order, family, genus, species) in session.species_under(subtree)? {
+        let vernacular = session
+            .entry(&species.latin)
+            .map(|entry| entry.english.clone())
+            .filter(|english| !english.is_empty())
+            .unwrap_or_else(|| species.chinese.clone());
+
+        for photo in species
+            .photos
+            .iter()
+            .filter(|photo| photo_matches(filter, photo))
+        {
+            let coordinates = photo.latitude.zip(photo.longitude);
+            let record: [String; 17] = [
+                format!("birdindex2:{:016x}", fnv1a(&photo.path)),
+                "HumanObservation".to_string(),
+                species.latin.clone(),
+                "species".to_string(),
+                "Animalia".to_string(),
+                "Aves".to_string(),
+                order.name.clone(),
+                family.name.clone(),
+                genus.name.clone(),
+                format!(
+                    "Animalia | Chordata | Aves | {} | {} | {}",
+                    order.name, family.name, genus.name
+                ),
+                vernacular.clone(),
+                // A file mtime is not an observation date, and a file name
+                // date has no time of day.
+                match (&photo.taken_at, photo.date_source) {
+                    (Some(taken), DateSource::Exif) => taken.clone(),
+                    (Some(taken), DateSource::FileName) => date_part(taken).to_string(),
+                    _ => String::new(),
+                },
+                coordinates
+                    .map(|(lat, _)| format!("{lat:.6}"))
+                    .unwrap_or_default(),
+                coordinates
+                    .map(|(_, lon)| format!("{lon:.6}"))
+                    .unwrap_or_default(),
+                coordinates.map(|_| "WGS84".to_string()).unwrap_or_default(),
+                photo.location.clone().unwrap_or_default(),
+                file_uri(&photo.path),
+            ];
+            let fields: Vec<String> = record
+                .iter()
+                .map(|value| value.replace(['\t', '\r', '\n'], " "))
+                .collect();
+            occurrences.push_str(&fields.join("\t"));
+            occurrences.push('\n');
+            count += 1;
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create export dir: {}", parent.display()))?;
+    }
+    let file = File::create(path)
+        .with_context(|| format!("Failed to write export file: {}", path.display()))?;
+    let mut archive = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    archive.start_file("occurrence.txt", options)?;
+    archive.write_all(occurrences.as_bytes())?;
+    archive.start_file("meta.xml", options)?;
+    archive.write_all(darwin_core_meta().as_bytes())?;
+    archive
+        .finish()
+        .with_context(|| format!("Failed to write export file: {}", path.display()))?;
+
+    Ok(count)
+}
+
+fn darwin_core_meta() -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core encoding="UTF-8" fieldsTerminatedBy="\t" linesTerminatedBy="\n" fieldsEnclosedBy="" ignoreHeaderLines="1" rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <id index="0"/>
+"#,
+    );
+    for (idx, term) in DWC_TERMS.iter().enumerate() {
+        xml.push_str(&format!(
+            "    <field index=\"{idx}\" term=\"{DWC_NAMESPACE}{term}\"/>\n"
+        ));
+    }
+    xml.push_str("  </core>\n</archive>\n");
+    xml
+}
+
+/// Builds a `file://` URI, resolving relative paths against the working directory.
+pub fn file_uri(path: &str) -> String {
+    let normalized = absolute_path(path).replace('\\', "/");
+    let prefix = if normalized.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    };
+    // A Windows drive letter keeps its colon.
+    let (drive, rest) = match normalized.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => normalized.split_at(2),
+        _ => ("", normalized.as_str()),
+    };
+    format!("{prefix}{drive}{}", url_escape(rest))
+}
+
+fn absolute_path(path: &str) -> String {
+    let is_absolute = match path.as_bytes() {
+        [b'/' | b'\\', ..] => true,
+        [letter, b':', ..] => letter.is_ascii_alphabetic(),
+        _ => false,
+    };
+    if is_absolute {
+        return path.to_string();
+    }
+    std::path::absolute(path)
+        .map(|absolute| absolute.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Percent-encodes every byte except RFC 3986 unreserved characters and `/`.
+pub fn url_escape(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_uri, to_csv, to_markdown};
+
+    #[test]
+    fn escapes_csv_fields() {
+        let records = [
+            ["plain".to_string(), "a,b".to_string()],
+            ["say \"hi\"".to_string(), "two\nlines".to_string()],
+        ];
+        assert_eq!(
+            to_csv(&["name", "note"], &records),
+            "\u{feff}name,note\r\nplain,\"a,b\"\r\n\"say \"\"hi\"\"\",\"two\nlines\"\r\n"
+        );
+    }
+
+    #[test]
+    fn escapes_markdown_cells() {
+        let records = [["a|b".to_string(), "two\r\nlines\nhere".to_string()]];
+        assert_eq!(
+            to_markdown(&["name", "note"], &records),
+            "| name | note |\n| --- | --- |\n| a\\|b | two<br>lines<br>here |\n"
+        );
+    }
+
+    #[test]
+    fn file_uri_keeps_absolute_paths() {
+        assert_eq!(file_uri("/photos/a b.jpg"), "file:///photos/a%20b.jpg");
+        assert_eq!(file_uri("C:\\Photos\\x.jpg"), "file:///C:/Photos/x.jpg");
+    }
+
+    #[test]
+    fn file_uri_resolves_relative_paths() {
+        let cwd = std::env::current_dir().unwrap();
+        let expected = file_uri(&cwd.join("photos/a b.jpg").to_string_lossy());
+        assert!(expected.ends_with("/photos/a%20b.jpg"));
+        assert_eq!(file_uri("photos/a b.jpg"), expected);
+        assert_eq!(
+            file_uri("./rel.jpg"),
+            file_uri(&cwd.join("rel.jpg").to_string_lossy())
+        );
+    }
+}
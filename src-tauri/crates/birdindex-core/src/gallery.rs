@@ -0,0 +1,349 @@
+#[cfg(feature = "thumbnails")]
+use crate::cache::file_mtime;
+use crate::export::{file_uri, url_escape};
+#[cfg(feature = "thumbnails")]
+use crate::metadata::exif_thumbnail;
+#[cfg(feature = "thumbnails")]
+use crate::preview::extract_embedded_jpeg;
+#[cfg(feature = "thumbnails")]
+use crate::scanner::is_raw;
+use crate::types::{GalleryLinks, PhotoItem, SpeciesNode, TaxonTree};
+#[cfg(feature = "thumbnails")]
+use crate::util::fnv1a;
+use anyhow::{anyhow, Context, Result};
+#[cfg(feature = "thumbnails")]
+use image::ImageFormat;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Longest side of a gallery thumbnail, in pixels.
+#[cfg(feature = "thumbnails")]
+const THUMBNAIL_SIZE: u32 = 360;
+
+const STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;margin:24px;color:#1f252f;background:#f7f5f0}
+a{color:#1e4a34}
+details{margin-left:16px}
+summary{cursor:pointer;padding:2px 0}
+ul{list-style:none;padding-left:16px;margin:4px 0}
+.muted{color:#5d6775}
+.grid{display:grid;grid-template-columns:repeat(auto-fill,minmax(180px,1fr));gap:12px}
+.photo{background:#fff;border-radius:10px;padding:8px;font-size:12px;word-break:break-all}
+.photo img{width:100%;height:140px;object-fit:cover;border-radius:6px;background:#e5e7eb}
+.placeholder{display:flex;align-items:center;justify-content:center;height:140px;border-radius:6px;background:#e5e7eb;color:#6b7280}
+";
+
+pub fn write_gallery(
+    tree: &TaxonTree,
+    roots: &[String],
+    output: &Path,
+    links: GalleryLinks,
+) -> Result<usize> {
+    // Pages and links are built from the canonical output, so relative
+    // links line up with the canonical photo paths.
+    let output = &canonical(output);
+    for root in roots {
+        if output.starts_with(canonical(Path::new(root))) {
+            return Err(anyhow!(
+                "Gallery output must not be inside a scanned folder: {}",
+                root
+            ));
+        }
+    }
+
+    let species_dir = output.join("species");
+    let thumb_dir = output.join("thumbnails");
+    fs::create_dir_all(&species_dir)
+        .with_context(|| format!("Failed to create gallery dir: {}", species_dir.display()))?;
+
+    fs::write(output.join("style.css"), STYLE)
+        .with_context(|| format!("Failed to write gallery: {}", output.display()))?;
+
+    let mut pages = 0usize;
+    let mut index = page_head("BirdIndex2", "style.css");
+    index.push_str(&format!(
+        "<h1>BirdIndex2</h1>\n<p class=\"muted\">{} / {} species</p>\n",
+        tree.photographed_species, tree.total_species
+    ));
+
+    for order in &tree.orders {
+        index.push_str(&format!(
+            "<details open><summary>{} ({})</summary>\n",
+            escape(&order.name),
+            order.count
+        ));
+        for family in &order.families {
+            index.push_str(&format!(
+                "<details><summary>{} ({})</summary>\n",
+                escape(&family.name),
+                family.count
+            ));
+            for genus in &family.genera {
+                index.push_str(&format!(
+                    "<details><summary><i>{}</i> ({})</summary>\n<ul>\n",
+                    escape(&genus.name),
+                    genus.count
+                ));
+                for species in &genus.species {
+                    let label = species_label(species);
+                    if species.count == 0 {
+                        index.push_str(&format!("<li class=\"muted\">{label} (0)</li>\n"));
+                        continue;
+                    }
+                    let page = format!("{}.html", slug(&species.latin));
+                    write_species_page(species, &species_dir.join(&page), &thumb_dir, links)?;
+                    pages += 1;
+                    index.push_str(&format!(
+                        "<li><a href=\"species/{}\">{label}</a> ({})</li>\n",
+                        escape(&page),
+                        species.count
+                    ));
+                }
+                index.push_str("</ul>\n</details>\n");
+            }
+            index.push_str("</details>\n");
+        }
+        index.push_str("</details>\n");
+    }
+    index.push_str("</body>\n</html>\n");
+
+    fs::write(output.join("index.html"), index)
+        .with_context(|| format!("Failed to write gallery: {}", output.display()))?;
+    Ok(pages)
+}
+
+fn write_species_page(
+    species: &SpeciesNode,
+    page: &Path,
+    thumb_dir: &Path,
+    links: GalleryLinks,
+) -> Result<()> {
+    let page_dir = page.parent().unwrap_or(Path::new("."));
+    let label = species_label(species);
+    let mut html = page_head(&species.latin, "../style.css");
+    html.push_str(&format!(
+        "<p><a href=\"../index.html\">&larr; BirdIndex2</a></p>\n<h1>{label}</h1>\n<p class=\"muted\">{} photos</p>\n<div class=\"grid\">\n",
+        species.count
+    ));
+
+    for photo in &species.photos {
+        let original = link_to(&canonical(Path::new(&photo.path)), page_dir, links);
+        let image = match thumbnail(photo, thumb_dir) {
+            Some(thumbnail) => format!(
+                "<img src=\"{}\" alt=\"{}\" loading=\"lazy\">",
+                escape(&link_to(&thumbnail, page_dir, GalleryLinks::Relative)),
+                escape(&photo.file_name)
+            ),
+            None => "<div class=\"placeholder\">No preview</div>".to_string(),
+        };
+        html.push_str(&format!(
+            "<div class=\"photo\"><a href=\"{}\">{image}</a><div>{}</div><div class=\"muted\">{}</div></div>\n",
+            escape(&original),
+            escape(&photo.file_name),
+            escape(&photo.taken_at.as_deref().unwrap_or("").replace('T', " "))
+        ));
+    }
+
+    html.push_str("</div>\n</body>\n</html>\n");
+    fs::write(page, html).with_context(|| format!("Failed to write gallery: {}", page.display()))
+}
+
+/// Writes a downscaled JPEG of the photo into `thumb_dir`, from the photo
+/// itself, the embedded preview of a RAW file or the EXIF thumbnail of a HEIC.
+#[cfg(feature = "thumbnails")]
+fn thumbnail(photo: &PhotoItem, thumb_dir: &Path) -> Option<PathBuf> {
+    let path = Path::new(&photo.path);
+    let key = format!("{}:{}", photo.path, file_mtime(path));
+    let target = thumb_dir.join(format!("{:016x}.jpg", fnv1a(&key)));
+    if target.exists() {
+        return Some(target);
+    }
+
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let decoded = if is_raw(path) {
+        image::load_from_memory(&extract_embedded_jpeg(path).ok()?).ok()?
+    } else if ext == "heic" {
+        image::load_from_memory(&exif_thumbnail(path)?).ok()?
+    } else {
+        image::open(path).ok()?
+    };
+    fs::create_dir_all(thumb_dir).ok()?;
+    decoded
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgb8()
+        .save_with_format(&target, ImageFormat::Jpeg)
+        .ok()?;
+    Some(target)
+}
+
+#[cfg(not(feature = "thumbnails"))]
+fn thumbnail(_photo: &PhotoItem, _thumb_dir: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// Resolves symlinks and `..` in the part of `path` that exists.
+fn canonical(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |resolved, name| resolved.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+fn link_to(target: &Path, from_dir: &Path, links: GalleryLinks) -> String {
+    match links {
+        GalleryLinks::Absolute => file_uri(&target.to_string_lossy()),
+        GalleryLinks::Relative => match relative_path(target, from_dir) {
+            Some(relative) => relative
+                .components()
+                .map(|component| url_escape(&component.as_os_str().to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("/"),
+            None => file_uri(&target.to_string_lossy()),
+        },
+    }
+}
+
+fn relative_path(target: &Path, from_dir: &Path) -> Option<PathBuf> {
+    let target: Vec<Component> = target.components().collect();
+    let from: Vec<Component> = from_dir.components().collect();
+    if target.first() != from.first() {
+        return None;
+    }
+
+    let common = target
+        .iter()
+        .zip(from.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component.as_os_str());
+    }
+    Some(relative)
+}
+
+fn page_head(title: &str, stylesheet: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{stylesheet}\">\n</head>\n<body>\n",
+        escape(title)
+    )
+}
+
+fn species_label(species: &SpeciesNode) -> String {
+    if species.chinese.is_empty() {
+        format!("<i>{}</i>", escape(&species.latin))
+    } else {
+        format!(
+            "{} <i>{}</i>",
+            escape(&species.chinese),
+            escape(&species.latin)
+        )
+    }
+}
+
+fn slug(latin: &str) -> String {
+    latin
+        .split_whitespace()
+        .map(|part| part.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical, relative_path, write_gallery};
+    use crate::tree::build_tree;
+    use crate::types::{
+        DateSource, GalleryLinks, Identification, IocEntry, MatchSource, MatchedPhoto, TreeOptions,
+    };
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn relative_links_mix_absolute_and_relative_paths() {
+        let dir = std::env::temp_dir().join(format!("birdindex-gallery-{}", std::process::id()));
+        fs::create_dir_all(dir.join("photos")).unwrap();
+        fs::write(dir.join("photos/a b.jpg"), b"not a jpeg").unwrap();
+        let cwd = canonical(&std::env::current_dir().unwrap());
+        let relative = |path: &Path| {
+            relative_path(&canonical(path), &cwd)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let entries = [IocEntry {
+            seq: 0,
+            order: "Passeriformes".to_string(),
+            family: "Pycnonotidae".to_string(),
+            latin: "Pycnonotus sinensis".to_string(),
+            chinese: String::new(),
+            english: "Light-vented Bulbul".to_string(),
+            subspecies: Vec::new(),
+            other_names: Vec::new(),
+        }];
+        let shown = |path: &Path, as_relative: bool| {
+            if as_relative {
+                relative(path)
+            } else {
+                path.to_string_lossy().into_owned()
+            }
+        };
+        // A relative output with absolute photo paths, and the other way round.
+        for relative_output in [true, false] {
+            let path = |path: &Path| shown(path, !relative_output);
+            let output = |path: &Path| shown(path, relative_output);
+            let photo = MatchedPhoto {
+                path: path(&dir.join("photos/a b.jpg")),
+                file_name: "a b.jpg".to_string(),
+                species_idx: 0,
+                identification: Identification::Species,
+                subspecies: None,
+                taken_at: None,
+                date_source: DateSource::Mtime,
+                latitude: None,
+                longitude: None,
+                location: None,
+                sequence: None,
+                source: MatchSource::Latin,
+                confidence: 100,
+            };
+            let tree = build_tree(&entries, &[photo], TreeOptions::default());
+            let roots = [path(&dir.join("photos"))];
+            let output = output(&dir.join("gallery"));
+            write_gallery(&tree, &roots, Path::new(&output), GalleryLinks::Relative).unwrap();
+
+            let page =
+                fs::read_to_string(dir.join("gallery/species/pycnonotus-sinensis.html")).unwrap();
+            assert!(page.contains("href=\"../../photos/a%20b.jpg\""), "{page}");
+        }
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,280 @@
+use crate::codes::AlphaCodes;
+use crate::types::{IocChange, IocDiff, IocEntry};
+#[cfg(feature = "xlsx")]
+use anyhow::{anyhow, Context, Result};
+#[cfg(feature = "xlsx")]
+use calamine::{open_workbook_auto, DataType, Range, Reader};
+use std::collections::HashMap;
+#[cfg(feature = "xlsx")]
+use std::path::Path;
+
+/// The IOC checklist, in list order, with a lowercase Latin name index.
+pub struct IocDatabase {
+    pub entries: Vec<IocEntry>,
+    pub latin_index: HashMap<String, usize>,
+}
+
+impl IocDatabase {
+    /// Builds a database from already parsed entries.
+    pub fn from_entries(entries: Vec<IocEntry>) -> Self {
+        let mut latin_index = HashMap::with_capacity(entries.len());
+        for (idx, entry) in entries.iter().enumerate() {
+            latin_index.insert(entry.latin.to_lowercase(), idx);
+        }
+
+        Self {
+            entries,
+            latin_index,
+        }
+    }
+
+    /// Generates four-letter (English) and six-letter (Latin) banding codes.
+    pub fn alpha_codes(&self) -> AlphaCodes {
+        AlphaCodes::generate(&self.entries)
+    }
+
+    /// Loads the `List` worksheet of a multilingual IOC workbook, plus
+    /// subspecies from a sheet named like `Subspecies` or `Master` if present.
+    #[cfg(feature = "xlsx")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut workbook = open_workbook_auto(path)
+            .with_context(|| format!("Failed to open IOC workbook: {}", path.display()))?;
+
+        let range = workbook
+            .worksheet_range("List")
+            .with_context(|| "Worksheet 'List' not found")?;
+
+        let mut rows = range.rows();
+        let header = rows
+            .next()
+            .ok_or_else(|| anyhow!("Worksheet 'List' is empty"))?;
+
+        let mut col_map = HashMap::new();
+        for (idx, cell) in header.iter().enumerate() {
+            if let Some(name) = cell_string(cell) {
+                col_map.insert(name, idx);
+            }
+        }
+
+        let order_col = *col_map
+            .get("Order")
+            .ok_or_else(|| anyhow!("Column 'Order' not found"))?;
+        let family_col = *col_map
+            .get("Family")
+            .ok_or_else(|| anyhow!("Column 'Family' not found"))?;
+        // Latin names are in a column per list version, e.g. `IOC_15.1`; the
+        // newest one wins.
+        let latin_col = col_map
+            .iter()
+            .filter_map(|(name, idx)| Some((ioc_version(name)?, *idx)))
+            .max()
+            .map(|(_, idx)| idx)
+            .ok_or_else(|| anyhow!("No Latin name column like 'IOC_15.1' found"))?;
+        let chinese_col = *col_map
+            .get("Chinese")
+            .ok_or_else(|| anyhow!("Column 'Chinese' not found"))?;
+        let english_col = col_map.get("English").copied();
+        let mut other_cols: Vec<usize> = col_map
+            .iter()
+            .filter(|(name, _)| {
+                !matches!(
+                    name.as_str(),
+                    "seq" | "Order" | "Family" | "English" | "Chinese"
+                ) && ioc_version(name).is_none()
+            })
+            .map(|(_, idx)| *idx)
+            .collect();
+        other_cols.sort_unstable();
+
+        let mut entries = Vec::new();
+        for (seq, row) in rows.enumerate() {
+            let order = cell_string(row.get(order_col).unwrap_or(&DataType::Empty));
+            let family = cell_string(row.get(family_col).unwrap_or(&DataType::Empty));
+            let latin = cell_string(row.get(latin_col).unwrap_or(&DataType::Empty));
+            let chinese =
+                cell_string(row.get(chinese_col).unwrap_or(&DataType::Empty)).unwrap_or_default();
+            let english = english_col
+                .and_then(|col| cell_string(row.get(col).unwrap_or(&DataType::Empty)))
+                .unwrap_or_default();
+
+            let (order, family, latin) = match (order, family, latin) {
+                (Some(order), Some(family), Some(latin)) => (order, family, latin),
+                _ => continue,
+            };
+
+            let latin = latin.trim().to_string();
+            if latin.is_empty() {
+                continue;
+            }
+            let chinese = chinese.trim().to_string();
+            let english = english.trim().to_string();
+
+            let mut other_names: Vec<String> = Vec::new();
+            for col in &other_cols {
+                let Some(name) = row.get(*col).and_then(cell_string) else {
+                    continue;
+                };
+                if !name.is_empty()
+                    && ![&latin, &chinese, &english].contains(&&name)
+                    && !other_names.contains(&name)
+                {
+                    other_names.push(name);
+                }
+            }
+
+            entries.push(IocEntry {
+                seq,
+                order: order.trim().to_string(),
+                family: family.trim().to_string(),
+                latin,
+                chinese,
+                english,
+                subspecies: Vec::new(),
+                other_names,
+            });
+        }
+
+        let sheet = workbook.sheet_names().into_iter().find(|name| {
+            let name = name.to_lowercase();
+            name.contains("subspecies") || name.contains("master")
+        });
+        if let Some(sheet) = sheet {
+            let range = workbook
+                .worksheet_range(&sheet)
+                .with_context(|| format!("Failed to read worksheet '{sheet}'"))?;
+            let mut subspecies = load_subspecies(&range);
+            for entry in &mut entries {
+                if let Some(epithets) = subspecies.remove(&entry.latin.to_lowercase()) {
+                    entry.subspecies = epithets;
+                }
+            }
+        }
+
+        Ok(Self::from_entries(entries))
+    }
+}
+
+/// The version of an `IOC_<major>.<minor>` column, e.g. `[15, 1]`.
+#[cfg(feature = "xlsx")]
+fn ioc_version(column: &str) -> Option<Vec<u32>> {
+    column
+        .strip_prefix("IOC_")?
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Reads subspecies from an IOC master-list sheet, keyed by lowercase
+/// binomial. Genus and species cells may be blank on subspecies rows and
+/// carry over from the rows above; species may be a full binomial or an
+/// epithet.
+#[cfg(feature = "xlsx")]
+fn load_subspecies(range: &Range<DataType>) -> HashMap<String, Vec<String>> {
+    let mut result: HashMap<String, Vec<String>> = HashMap::new();
+    let mut rows = range.rows();
+    let Some(header) = rows.next() else {
+        return result;
+    };
+
+    let headers: Vec<String> = header
+        .iter()
+        .map(|cell| cell_string(cell).unwrap_or_default().to_lowercase())
+        .collect();
+    let find = |matches: &dyn Fn(&str) -> bool| headers.iter().position(|name| matches(name));
+    let Some(subspecies_col) = find(&|name| name.starts_with("subspecies")) else {
+        return result;
+    };
+    let genus_col = find(&|name| name == "genus");
+    let species_col = find(&|name| name.starts_with("species"));
+
+    let mut genus = String::new();
+    let mut species = String::new();
+    for row in rows {
+        let cell = |col: Option<usize>| {
+            col.and_then(|col| row.get(col))
+                .and_then(cell_string)
+                .filter(|value| !value.is_empty())
+        };
+        if let Some(value) = cell(genus_col) {
+            genus = value;
+        }
+        if let Some(value) = cell(species_col) {
+            species = if value.contains(' ') || genus.is_empty() {
+                value
+            } else {
+                format!("{genus} {value}")
+            };
+        }
+        let Some(epithet) = cell(Some(subspecies_col))
+            .and_then(|value| value.split_whitespace().last().map(str::to_lowercase))
+        else {
+            continue;
+        };
+        if species.is_empty() {
+            continue;
+        }
+        let epithets = result.entry(species.to_lowercase()).or_default();
+        if !epithets.contains(&epithet) {
+            epithets.push(epithet);
+        }
+    }
+    result
+}
+
+/// Compares two checklists by Latin name.
+pub fn diff(old: &IocDatabase, new: &IocDatabase) -> IocDiff {
+    let mut result = IocDiff::default();
+
+    for entry in &new.entries {
+        match old.latin_index.get(&entry.latin.to_lowercase()) {
+            None => result.added.push(entry.clone()),
+            Some(&idx) => {
+                let before = &old.entries[idx];
+                let mut fields = Vec::new();
+                if before.order != entry.order {
+                    fields.push("order".to_string());
+                }
+                if before.family != entry.family {
+                    fields.push("family".to_string());
+                }
+                if before.chinese != entry.chinese {
+                    fields.push("chinese".to_string());
+                }
+                if before.english != entry.english {
+                    fields.push("english".to_string());
+                }
+                if !fields.is_empty() {
+                    result.changed.push(IocChange {
+                        before: before.clone(),
+                        after: entry.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    for entry in &old.entries {
+        if !new.latin_index.contains_key(&entry.latin.to_lowercase()) {
+            result.removed.push(entry.clone());
+        }
+    }
+
+    result
+}
+
+#[cfg(feature = "xlsx")]
+fn cell_string(cell: &DataType) -> Option<String> {
+    match cell {
+        DataType::String(value) => Some(value.trim().to_string()),
+        DataType::Float(value) => Some(format!("{}", value)),
+        DataType::Int(value) => Some(value.to_string()),
+        DataType::Bool(value) => Some(value.to_string()),
+        DataType::Error(_) | DataType::Empty => None,
+        DataType::DateTime(value) => Some(value.to_string()),
+        DataType::Duration(value) => Some(value.to_string()),
+        DataType::DateTimeIso(value) => Some(value.to_string()),
+        DataType::DurationIso(value) => Some(value.to_string()),
+    }
+}
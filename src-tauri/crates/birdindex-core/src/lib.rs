@@ -0,0 +1,130 @@
+//! Core of BirdIndex: IOC checklist loading, filename matching with user
+//! aliases, manual overrides, photo scanning with an incremental cache, taxon
+//! tree building and exports.
+//!
+//! Cargo features (all on by default):
+//! - `xlsx`: read the IOC workbook and write XLSX life lists.
+//! - `exif`: read capture time and GPS from EXIF, otherwise only mtime is used.
+//! - `thumbnails`: extract embedded JPEG previews from RAW files and write
+//!   gallery thumbnails.
+//! - `chinese`: derive Traditional and pinyin forms of Chinese names.
+
+pub mod aliases;
+pub mod cache;
+pub mod chinese;
+pub mod codes;
+pub mod export;
+pub mod gallery;
+pub mod ioc;
+pub mod matcher;
+pub mod metadata;
+pub mod normalize;
+pub mod overrides;
+#[cfg(feature = "thumbnails")]
+pub mod preview;
+pub mod scanner;
+pub mod search;
+pub mod session;
+pub mod template;
+pub mod tree;
+pub mod types;
+pub mod util;
+
+#[cfg(feature = "xlsx")]
+use aliases::aliases_path;
+use aliases::AliasDictionary;
+use anyhow::Result;
+#[cfg(feature = "xlsx")]
+use cache::fingerprint;
+use cache::{load_cache, save_cache};
+use ioc::IocDatabase;
+use matcher::NameMatcher;
+use overrides::OverrideStore;
+use scanner::scan_paths;
+use session::ScanSession;
+use std::path::Path;
+use template::FileTemplates;
+use tree::build_tree;
+use types::{ExclusionOptions, MatchOptions, ScanRequest};
+
+/// Loads the IOC workbook and the alias file next to it, scans
+/// `request.roots` and builds a session.
+#[cfg(feature = "xlsx")]
+pub fn scan_and_build(
+    request: ScanRequest,
+    ioc_path: &Path,
+    cache_path: &Path,
+    overrides: &OverrideStore,
+) -> Result<ScanSession> {
+    let ioc = IocDatabase::load(ioc_path)?;
+    let ioc_fingerprint = fingerprint(ioc_path)?;
+    let aliases = AliasDictionary::load(aliases_path(ioc_path), &ioc.latin_index)?;
+    scan_with_ioc(
+        request,
+        ioc,
+        &ioc_fingerprint,
+        &aliases,
+        cache_path,
+        overrides,
+    )
+}
+
+/// Scans with an already loaded checklist. `ioc_fingerprint` keys the cache,
+/// so it must change whenever the checklist does.
+pub fn scan_with_ioc(
+    request: ScanRequest,
+    ioc: IocDatabase,
+    ioc_fingerprint: &str,
+    aliases: &AliasDictionary,
+    cache_path: &Path,
+    overrides: &OverrideStore,
+) -> Result<ScanSession> {
+    // Matches depend on the options, aliases, templates and exclusions as
+    // well as the checklist.
+    let cache_key = if request.matching == MatchOptions::default()
+        && aliases.is_empty()
+        && request.templates.is_empty()
+        && request.exclusions == ExclusionOptions::default()
+    {
+        ioc_fingerprint.to_string()
+    } else {
+        serde_json::json!({
+            "ioc": ioc_fingerprint,
+            "matching": request.matching,
+            "aliases": aliases.fingerprint(),
+            "templates": request.templates,
+            "exclusions": request.exclusions,
+        })
+        .to_string()
+    };
+    let templates = FileTemplates::compile(&request.templates)?;
+    let cache = load_cache(cache_path, &cache_key)?;
+    let matcher = NameMatcher::with_aliases(&ioc.entries, request.matching, aliases)
+        .with_exclusions(&request.exclusions);
+
+    let output = scan_paths(
+        &request.roots,
+        &ioc.entries,
+        &ioc.latin_index,
+        &matcher,
+        &cache,
+        overrides,
+        &templates,
+    );
+    let tree = build_tree(&ioc.entries, &output.matches, request.tree);
+    let override_problems = overrides.problems(&ioc.latin_index);
+
+    let mut unmatched = output.unmatched;
+    unmatched.sort();
+    let mut vetoed = output.vetoed;
+    vetoed.sort_by(|a, b| a.path.cmp(&b.path));
+
+    save_cache(cache_path, &cache_key, output.cache_entries)?;
+
+    let mut session = ScanSession::new(tree, output.stats, request.roots, unmatched, ioc.entries);
+    session.index_aliases(aliases);
+    session.alias_problems = aliases.problems.clone();
+    session.override_problems = override_problems;
+    session.vetoed = vetoed;
+    Ok(session)
+}
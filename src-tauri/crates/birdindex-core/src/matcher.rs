@@ -0,0 +1,693 @@
+use crate::aliases::AliasDictionary;
+use crate::chinese::chinese_forms;
+use crate::codes::AlphaCodes;
+use crate::normalize::{normalize, normalize_words};
+use crate::tree::genus_name;
+use crate::types::{
+    CombinationKind, ExclusionOptions, Identification, IocEntry, MatchOptions, MatchSource,
+};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use std::collections::HashMap;
+
+/// Finds the IOC species named in a file name. Latin names are tried first,
+/// with any separator or in CamelCase, then Chinese, then user aliases, then
+/// the opt-in English names, banding codes, Traditional and pinyin forms, then
+/// a genus followed by `sp.`/`spp.`, and last the opt-in Latin names with one
+/// typo.
+/// A second name joined by `x`/`×` (hybrid) or `/`/`or` (pair) is read too.
+/// Optional exclusions mask never-match text and veto hits in file names
+/// containing certain words.
+/// Each hit carries a confidence score, see [`confidence`].
+pub struct NameMatcher {
+    tiers: Vec<PatternTier>,
+    latin_names: Vec<String>,
+    latin_index: HashMap<String, usize>,
+    genus_tier: Option<PatternTier>,
+    genus_index: HashMap<String, usize>,
+    /// Entries by genus and by epithet, for typos in the other word. Empty
+    /// unless `fuzzy_latin` is set.
+    by_genus: HashMap<String, Vec<usize>>,
+    by_epithet: HashMap<String, Vec<usize>>,
+    strip_accents: bool,
+    /// The IOC subspecies epithets of each entry.
+    subspecies: Vec<Vec<String>>,
+    /// Each never-match pattern as given, as a name and in word form.
+    never_match: Vec<(String, String, String)>,
+    /// Each veto word as given and in word form.
+    veto_words: Vec<(String, String)>,
+}
+
+/// A species hit, with the subspecies epithet that followed a Latin name.
+/// Genus-only hits point at the first IOC entry of the genus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameMatch {
+    pub species_idx: usize,
+    pub subspecies: Option<String>,
+    pub identification: Identification,
+    pub source: MatchSource,
+    /// 0–100, see [`confidence`].
+    pub confidence: u8,
+}
+
+/// A hit rejected by the exclusions, kept for diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Veto {
+    pub species_idx: usize,
+    pub reason: String,
+}
+
+/// Replaces never-match text, so no pattern can match across it.
+const MASK: &str = "\u{1}";
+
+/// Shortest word a fuzzy Latin match corrects.
+const MIN_FUZZY_CHARS: usize = 5;
+
+struct PatternTier {
+    automaton: AhoCorasick,
+    map: Vec<usize>,
+    source: MatchSource,
+    split_words: bool,
+    /// Only accept hits not running into ASCII letters or digits, so short
+    /// aliases like `lvb` do not fire inside other words.
+    bounded: bool,
+}
+
+impl NameMatcher {
+    pub fn new(entries: &[IocEntry], options: MatchOptions) -> Self {
+        Self::with_aliases(entries, options, &AliasDictionary::empty())
+    }
+
+    /// Like [`NameMatcher::new`], also matching the aliases of a dictionary
+    /// built from the same entries.
+    pub fn with_aliases(
+        entries: &[IocEntry],
+        options: MatchOptions,
+        aliases: &AliasDictionary,
+    ) -> Self {
+        let mut latin = Vec::new();
+        let mut latin_names = Vec::with_capacity(entries.len());
+        let mut chinese = Vec::new();
+        let mut english = Vec::new();
+        let mut traditional = Vec::new();
+        let mut pinyin = Vec::new();
+        let mut initials = Vec::new();
+
+        let strip_accents = options.strip_accents;
+        let variants = options.traditional || options.pinyin || options.pinyin_initials;
+        for (idx, entry) in entries.iter().enumerate() {
+            let name = entry.latin.trim();
+            let pattern = normalize(name, strip_accents);
+            if !name.is_empty() {
+                if pattern.contains(' ') {
+                    latin.push((pattern.replace(' ', ""), idx));
+                }
+                latin.push((pattern.clone(), idx));
+            }
+            latin_names.push(pattern);
+            if options.english_names {
+                let name = normalize_words(&entry.english, strip_accents)
+                    .trim()
+                    .to_string();
+                if !name.is_empty() {
+                    english.push((name, idx));
+                }
+            }
+            let name = entry.chinese.trim();
+            if !name.is_empty() {
+                chinese.push((normalize(name, strip_accents), idx));
+            }
+            if !variants {
+                continue;
+            }
+
+            let forms = chinese_forms(name);
+            if options.traditional && !forms.traditional.is_empty() {
+                traditional.push((normalize(&forms.traditional, strip_accents), idx));
+            }
+            if options.pinyin && !forms.pinyin.is_empty() {
+                pinyin.push((forms.pinyin, idx));
+            }
+            if options.pinyin_initials && !forms.initials.is_empty() {
+                initials.push((forms.initials, idx));
+            }
+        }
+
+        let alias_names = aliases
+            .aliases
+            .iter()
+            .map(|(alias, idx)| (normalize_words(alias, strip_accents), *idx))
+            .filter(|(alias, _)| !alias.is_empty())
+            .collect();
+        // Ambiguous codes are left out; `AlphaCodes::lookup` reports them.
+        let codes = if options.alpha_codes {
+            AlphaCodes::generate(entries)
+                .unique()
+                .map(|(code, idx)| (code.to_ascii_lowercase(), idx))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let tiers = [
+            (latin, MatchSource::Latin, true, false),
+            (chinese, MatchSource::Chinese, false, false),
+            (alias_names, MatchSource::Alias, true, true),
+            (english, MatchSource::Keyword, true, true),
+            (codes, MatchSource::Code, true, true),
+            (traditional, MatchSource::Traditional, false, false),
+            (pinyin, MatchSource::Pinyin, false, true),
+            (initials, MatchSource::PinyinInitials, false, true),
+        ]
+        .into_iter()
+        .filter_map(|(patterns, source, split_words, bounded)| {
+            PatternTier::build(patterns, source, split_words, bounded)
+        })
+        .collect();
+        let genus_index = genus_index(entries);
+        let genus_tier = PatternTier::build(
+            genus_patterns(&genus_index, aliases, strip_accents),
+            MatchSource::Genus,
+            true,
+            false,
+        );
+        let subspecies: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .subspecies
+                    .iter()
+                    .map(|epithet| normalize(epithet, strip_accents))
+                    .collect()
+            })
+            .collect();
+        let latin_index = latin_names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(idx, name)| (name.clone(), idx))
+            .collect();
+        let mut by_genus: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_epithet: HashMap<String, Vec<usize>> = HashMap::new();
+        if options.fuzzy_latin {
+            for (idx, name) in latin_names.iter().enumerate() {
+                if let Some((genus, epithet)) = name.split_once(' ') {
+                    by_genus.entry(genus.to_string()).or_default().push(idx);
+                    by_epithet.entry(epithet.to_string()).or_default().push(idx);
+                }
+            }
+        }
+        Self {
+            tiers,
+            latin_names,
+            latin_index,
+            genus_tier,
+            genus_index,
+            by_genus,
+            by_epithet,
+            strip_accents,
+            subspecies,
+            never_match: Vec::new(),
+            veto_words: Vec::new(),
+        }
+    }
+
+    pub fn with_exclusions(mut self, exclusions: &ExclusionOptions) -> Self {
+        let strip_accents = self.strip_accents;
+        self.never_match = exclusions
+            .never_match
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                (
+                    pattern.to_string(),
+                    normalize(pattern, strip_accents),
+                    normalize_words(pattern, strip_accents).trim().to_string(),
+                )
+            })
+            .filter(|(_, name, words)| !name.is_empty() && !words.is_empty())
+            .collect();
+        self.veto_words = exclusions
+            .veto_words
+            .iter()
+            .map(|word| {
+                let form = normalize_words(word, strip_accents).trim().to_string();
+                (word.trim().to_string(), form)
+            })
+            .filter(|(_, form)| !form.is_empty())
+            .collect();
+        self
+    }
+
+    /// Returns the index into the entries the matcher was built from.
+    pub fn match_name(&self, file_name: &str) -> Option<usize> {
+        self.match_file(file_name).map(|hit| hit.species_idx)
+    }
+
+    pub fn match_file(&self, file_name: &str) -> Option<NameMatch> {
+        self.classify(file_name).ok().flatten()
+    }
+
+    /// Like [`NameMatcher::match_file`], but reports a hit the exclusions
+    /// rejected instead of dropping it.
+    pub fn classify(&self, file_name: &str) -> Result<Option<NameMatch>, Veto> {
+        self.classify_in_folder(file_name, None)
+    }
+
+    /// Like [`NameMatcher::classify`], with `folder` the hit of a folder
+    /// above the file. A folder naming the same species scores the file's hit
+    /// up and one naming another species scores it down; a file name without
+    /// a hit takes the folder's as a [`MatchSource::Folder`] hit.
+    pub fn classify_in_folder(
+        &self,
+        file_name: &str,
+        folder: Option<&NameMatch>,
+    ) -> Result<Option<NameMatch>, Veto> {
+        let folder_species = folder
+            .filter(|hit| hit.identification == Identification::Species)
+            .map(|hit| hit.species_idx);
+        let name = normalize(file_name, self.strip_accents);
+        let words = normalize_words(file_name, self.strip_accents);
+
+        let mut masked_name = name.clone();
+        let mut masked_words = words.clone();
+        for (_, name_form, word_form) in &self.never_match {
+            masked_name = masked_name.replace(name_form, MASK);
+            masked_words = masked_words.replace(word_form, MASK);
+        }
+        let hit = match self.find(&masked_name, &masked_words, folder_species) {
+            Some(hit) => hit,
+            None => {
+                let masked = masked_name != name || masked_words != words;
+                if let Some(hit) = self.find(&name, &words, None).filter(|_| masked) {
+                    let pattern = self
+                        .never_match
+                        .iter()
+                        .find(|(_, name_form, word_form)| {
+                            name.contains(name_form.as_str()) || words.contains(word_form.as_str())
+                        })
+                        .map(|(pattern, _, _)| pattern.as_str())
+                        .unwrap_or_default();
+                    return Err(Veto {
+                        species_idx: hit.species_idx,
+                        reason: format!("never match: {pattern}"),
+                    });
+                }
+                match folder {
+                    Some(folder) => NameMatch {
+                        source: MatchSource::Folder,
+                        confidence: folder.confidence.min(confidence(
+                            MatchSource::Folder,
+                            true,
+                            false,
+                            false,
+                        )),
+                        ..folder.clone()
+                    },
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        if let Some((word, _)) = self
+            .veto_words
+            .iter()
+            .find(|(_, form)| contains_token(&words, form))
+        {
+            return Err(Veto {
+                species_idx: hit.species_idx,
+                reason: format!("veto word: {word}"),
+            });
+        }
+        Ok(Some(hit))
+    }
+
+    fn find(&self, name: &str, words: &str, folder: Option<usize>) -> Option<NameMatch> {
+        self.tiers
+            .iter()
+            .find_map(|tier| {
+                let haystack = if tier.split_words { words } else { name };
+                let hit = tier.find(haystack)?;
+                let species_idx = tier.map.get(hit.pattern().as_usize()).copied()?;
+                let rest = &haystack[hit.end()..];
+                let second = self.combination(tier, rest, species_idx);
+                let named: &[usize] = match &second {
+                    Second::Species(_, other) => &[species_idx, *other],
+                    _ => &[species_idx],
+                };
+                let (agreed, disputed) = self.agreement(name, words, tier.source, named, folder);
+                // A hybrid with a second name not in the checklist keeps the
+                // first species, scored as if another name disputed it.
+                let disputed = disputed || matches!(second, Second::Unresolved);
+                let clean_edges = has_clean_edges(haystack, hit.start(), hit.end());
+                let confidence = confidence(tier.source, clean_edges, agreed, disputed);
+                if let Second::Species(kind, other) = second {
+                    return Some(NameMatch {
+                        species_idx,
+                        subspecies: None,
+                        identification: Identification::Combination { kind, other },
+                        source: tier.source,
+                        confidence,
+                    });
+                }
+                let subspecies = if tier.split_words && !tier.bounded {
+                    self.trailing_epithet(rest, species_idx)
+                } else {
+                    None
+                };
+                Some(NameMatch {
+                    species_idx,
+                    subspecies,
+                    identification: Identification::Species,
+                    source: tier.source,
+                    confidence,
+                })
+            })
+            .or_else(|| self.match_genus(words))
+            .or_else(|| self.match_fuzzy(words))
+    }
+
+    /// Whether another Latin, Chinese, alias or code tier or the `folder`
+    /// species names one of the `named` species, and whether one names a
+    /// different species. The derived Traditional and pinyin forms do not
+    /// count.
+    fn agreement(
+        &self,
+        name: &str,
+        words: &str,
+        source: MatchSource,
+        named: &[usize],
+        folder: Option<usize>,
+    ) -> (bool, bool) {
+        let mut agreed = false;
+        let mut disputed = false;
+        for tier in &self.tiers {
+            let independent = matches!(
+                tier.source,
+                MatchSource::Latin
+                    | MatchSource::Chinese
+                    | MatchSource::Alias
+                    | MatchSource::Keyword
+                    | MatchSource::Code
+            );
+            if tier.source == source || !independent {
+                continue;
+            }
+            let haystack = if tier.split_words { words } else { name };
+            let Some(hit) = tier.find(haystack) else {
+                continue;
+            };
+            match tier.map.get(hit.pattern().as_usize()) {
+                Some(idx) if named.contains(idx) => agreed = true,
+                Some(_) => disputed = true,
+                None => {}
+            }
+        }
+        match folder {
+            Some(idx) if named.contains(&idx) => agreed = true,
+            Some(_) => disputed = true,
+            None => {}
+        }
+        (agreed, disputed)
+    }
+
+    /// Returns the entry standing in for a genus, as recorded in the cache.
+    pub fn genus_entry(&self, genus: &str) -> Option<usize> {
+        self.genus_index.get(&genus.to_lowercase()).copied()
+    }
+
+    fn match_genus(&self, words: &str) -> Option<NameMatch> {
+        let tier = self.genus_tier.as_ref()?;
+        tier.automaton.find_iter(words).find_map(|hit| {
+            let before = words[..hit.start()].chars().next_back();
+            if before.is_some_and(char::is_alphanumeric) {
+                return None;
+            }
+            let rest = words[hit.end()..].trim_start_matches(' ');
+            let marker = rest
+                .strip_prefix("spp")
+                .or_else(|| rest.strip_prefix("sp"))?;
+            if marker.starts_with(char::is_alphabetic) {
+                return None;
+            }
+            Some(NameMatch {
+                species_idx: tier.map.get(hit.pattern().as_usize()).copied()?,
+                subspecies: None,
+                identification: Identification::Genus,
+                source: MatchSource::Genus,
+                confidence: confidence(MatchSource::Genus, true, false, false),
+            })
+        })
+    }
+
+    /// Finds two words that are a binomial with one typo in either word, when
+    /// only one entry is that close. Words shorter than
+    /// `MIN_FUZZY_CHARS` are never corrected.
+    fn match_fuzzy(&self, words: &str) -> Option<NameMatch> {
+        if self.by_genus.is_empty() {
+            return None;
+        }
+        let tokens: Vec<&str> = words.split(' ').filter(|word| !word.is_empty()).collect();
+        tokens.windows(2).find_map(|pair| {
+            let (genus, epithet) = (pair[0], pair[1]);
+            let epithet_typos = self.by_genus.get(genus).into_iter().flatten().map(|&idx| {
+                let name = &self.latin_names[idx];
+                (idx, epithet, &name[genus.len() + 1..])
+            });
+            let genus_typos = self
+                .by_epithet
+                .get(epithet)
+                .into_iter()
+                .flatten()
+                .map(|&idx| {
+                    let name = &self.latin_names[idx];
+                    (idx, genus, &name[..name.len() - epithet.len() - 1])
+                });
+            let mut found = None;
+            for (idx, typed, name) in epithet_typos.chain(genus_typos) {
+                if !one_typo(typed, name) {
+                    continue;
+                }
+                if found.is_some_and(|found| found != idx) {
+                    return None;
+                }
+                found = Some(idx);
+            }
+            Some(NameMatch {
+                species_idx: found?,
+                subspecies: None,
+                identification: Identification::Species,
+                source: MatchSource::Fuzzy,
+                confidence: confidence(MatchSource::Fuzzy, true, false, false),
+            })
+        })
+    }
+
+    /// Reads `x <name>`, `× <name>`, `/<name>` or `or <name>` after a hit. In
+    /// Latin the second name may be just an epithet of the same genus. A bare
+    /// `or` is only a marker when a checklist name follows it.
+    fn combination(&self, tier: &PatternTier, rest: &str, species_idx: usize) -> Second {
+        let Some((kind, marker, rest)) = combination_marker(rest) else {
+            return Second::None;
+        };
+        if !rest.starts_with(char::is_alphabetic) {
+            return Second::None;
+        }
+
+        let other = match tier.find(rest) {
+            Some(hit) if hit.start() == 0 => tier.map.get(hit.pattern().as_usize()).copied(),
+            _ if tier.split_words && !tier.bounded => {
+                let genus = genus_name(&self.latin_names[species_idx]);
+                let epithet = rest.split(' ').next().unwrap_or_default();
+                self.latin_index.get(&format!("{genus} {epithet}")).copied()
+            }
+            _ => None,
+        };
+        match other {
+            Some(other) if other == species_idx => Second::None,
+            Some(other) => Second::Species(kind, other),
+            None if marker == "or" => Second::None,
+            None => Second::Unresolved,
+        }
+    }
+
+    /// The word after a binomial, if it is one of the species' IOC
+    /// subspecies.
+    fn trailing_epithet(&self, rest: &str, species_idx: usize) -> Option<String> {
+        let word = rest.strip_prefix(' ')?.split(' ').next()?;
+        self.subspecies[species_idx]
+            .iter()
+            .find(|epithet| *epithet == word)
+            .cloned()
+    }
+}
+
+/// Scores a hit from 0 to 100: the base score of its source, 15 less when the
+/// name runs into other letters, 10 more when another tier names the same
+/// species and 20 less when one names a different species.
+pub fn confidence(source: MatchSource, clean_edges: bool, agreed: bool, disputed: bool) -> u8 {
+    let base: i32 = match source {
+        MatchSource::Override => 100,
+        MatchSource::Latin => 90,
+        MatchSource::Chinese => 85,
+        MatchSource::Alias | MatchSource::Traditional => 80,
+        MatchSource::Keyword => 75,
+        MatchSource::Code => 70,
+        MatchSource::Pinyin => 65,
+        MatchSource::Genus => 60,
+        MatchSource::Fuzzy => 55,
+        MatchSource::Folder => 50,
+        MatchSource::PinyinInitials => 40,
+    };
+    let score = base - if clean_edges { 0 } else { 15 } + if agreed { 10 } else { 0 }
+        - if disputed { 20 } else { 0 };
+    score.clamp(0, 100) as u8
+}
+
+/// Whether `typed` is `name` with one letter added, dropped or changed, both
+/// at least `MIN_FUZZY_CHARS` long.
+fn one_typo(typed: &str, name: &str) -> bool {
+    let typed: Vec<char> = typed.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    if typed.len().min(name.len()) < MIN_FUZZY_CHARS
+        || typed.len().abs_diff(name.len()) > 1
+        || !typed.iter().all(|c| c.is_alphabetic())
+    {
+        return false;
+    }
+    let prefix = typed.iter().zip(&name).take_while(|(a, b)| a == b).count();
+    let suffix = typed[prefix..]
+        .iter()
+        .rev()
+        .zip(name[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    typed.len() - prefix - suffix <= 1 && name.len() - prefix - suffix <= 1
+}
+
+/// What follows a species hit.
+enum Second {
+    None,
+    /// A hybrid or pair marker and a second checklist species.
+    Species(CombinationKind, usize),
+    /// A hybrid or pair marker and a name not in the checklist.
+    Unresolved,
+}
+
+/// Splits a hybrid or pair marker off the start of `rest`, returning the
+/// marker and the text after it.
+fn combination_marker(rest: &str) -> Option<(CombinationKind, &'static str, &str)> {
+    let is_gap = |c: char| matches!(c, ' ' | '_' | '-' | '.' | '+');
+    let rest = rest.trim_start_matches(is_gap);
+    let (kind, marker) = [
+        (CombinationKind::Hybrid, "×"),
+        (CombinationKind::Pair, "/"),
+        (CombinationKind::Hybrid, "x"),
+        (CombinationKind::Pair, "or"),
+    ]
+    .into_iter()
+    .find(|(_, marker)| rest.starts_with(marker))?;
+    let rest = &rest[marker.len()..];
+    if marker.starts_with(|c: char| c.is_ascii_alphabetic())
+        && rest.starts_with(|c: char| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    Some((kind, marker, rest.trim_start_matches(is_gap)))
+}
+
+/// Whether `haystack[start..end]` has no letters, including Chinese
+/// characters, right before or after it. Digits and separators are fine.
+fn has_clean_edges(haystack: &str, start: usize, end: usize) -> bool {
+    let before = haystack[..start].chars().next_back();
+    let after = haystack[end..].chars().next();
+    !before.is_some_and(char::is_alphabetic) && !after.is_some_and(char::is_alphabetic)
+}
+
+/// Whether `haystack[start..end]` does not run into ASCII letters or digits on
+/// either side. Non-ASCII text such as Chinese has no word breaks to check.
+fn is_token(haystack: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    let matched = &haystack[start..end];
+    let before = haystack[..start].chars().next_back();
+    let after = haystack[end..].chars().next();
+    let runs_on = is_word(matched.chars().next()) && is_word(before);
+    let runs_into = is_word(matched.chars().next_back()) && is_word(after);
+    !runs_on && !runs_into
+}
+
+fn contains_token(haystack: &str, token: &str) -> bool {
+    haystack
+        .match_indices(token)
+        .any(|(start, _)| is_token(haystack, start, start + token.len()))
+}
+
+/// Maps each lowercase genus to its first IOC entry.
+fn genus_index(entries: &[IocEntry]) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        if !entry.latin.trim().is_empty() {
+            index
+                .entry(genus_name(&entry.latin).to_lowercase())
+                .or_insert(idx);
+        }
+    }
+    index
+}
+
+/// Latin genus names, plus the genus aliases of the dictionary, e.g. 柳莺
+/// for Phylloscopus.
+fn genus_patterns(
+    genus_index: &HashMap<String, usize>,
+    aliases: &AliasDictionary,
+    strip_accents: bool,
+) -> Vec<(String, usize)> {
+    let mut patterns: Vec<(String, usize)> = genus_index
+        .iter()
+        .map(|(genus, &idx)| (normalize(genus, strip_accents), idx))
+        .chain(
+            aliases
+                .genera
+                .iter()
+                .map(|(alias, idx)| (normalize_words(alias, strip_accents), *idx)),
+        )
+        .filter(|(name, _)| !name.is_empty())
+        .collect();
+    // Longer names first, so `Anser` never shadows a genus it prefixes.
+    patterns.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    patterns
+}
+
+impl PatternTier {
+    fn build(
+        patterns: Vec<(String, usize)>,
+        source: MatchSource,
+        split_words: bool,
+        bounded: bool,
+    ) -> Option<Self> {
+        if patterns.is_empty() {
+            return None;
+        }
+        let (patterns, map): (Vec<String>, Vec<usize>) = patterns.into_iter().unzip();
+        let automaton = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostFirst)
+            .build(patterns)
+            .ok()?;
+        Some(Self {
+            automaton,
+            map,
+            source,
+            split_words,
+            bounded,
+        })
+    }
+
+    fn find(&self, haystack: &str) -> Option<aho_corasick::Match> {
+        if !self.bounded {
+            return self.automaton.find(haystack);
+        }
+        self.automaton
+            .find_iter(haystack)
+            .find(|hit| is_token(haystack, hit.start(), hit.end()))
+    }
+}
@@ -0,0 +1,141 @@
+use crate::cache::file_mtime;
+use crate::types::DateSource;
+use chrono::{DateTime, Local};
+#[cfg(feature = "exif")]
+use exif::{Exif, In, Reader, Tag, Value};
+#[cfg(feature = "exif")]
+use std::fs::File;
+#[cfg(feature = "exif")]
+use std::io::BufReader;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct PhotoMetadata {
+    pub taken_at: Option<String>,
+    pub date_source: DateSource,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Reads capture time and GPS position. Without an EXIF capture time, uses
+/// `fallback_taken_at` (e.g. a date from the file name), then the file mtime
+/// in local time.
+pub fn read_metadata(path: &Path, fallback_taken_at: Option<String>) -> PhotoMetadata {
+    let mut metadata = exif_metadata(path);
+    if metadata.taken_at.is_some() {
+        metadata.date_source = DateSource::Exif;
+    } else if fallback_taken_at.is_some() {
+        metadata.taken_at = fallback_taken_at;
+        metadata.date_source = DateSource::FileName;
+    } else {
+        let mtime = file_mtime(path);
+        metadata.taken_at = (mtime > 0).then_some(mtime).and_then(format_local);
+        metadata.date_source = DateSource::Mtime;
+    }
+    metadata
+}
+
+#[cfg(feature = "exif")]
+fn exif_metadata(path: &Path) -> PhotoMetadata {
+    read_exif(path)
+        .map(|exif| PhotoMetadata {
+            taken_at: exif_capture_time(&exif),
+            date_source: DateSource::Exif,
+            latitude: exif_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+            longitude: exif_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "exif"))]
+fn exif_metadata(_path: &Path) -> PhotoMetadata {
+    PhotoMetadata::default()
+}
+
+/// The JPEG thumbnail stored in the EXIF data, e.g. of a HEIC photo.
+#[cfg(feature = "exif")]
+pub fn exif_thumbnail(path: &Path) -> Option<Vec<u8>> {
+    let exif = read_exif(path)?;
+    let field = |tag| {
+        let value = exif.get_field(tag, In::THUMBNAIL)?.value.get_uint(0)?;
+        usize::try_from(value).ok()
+    };
+    let offset = field(Tag::JPEGInterchangeFormat)?;
+    let length = field(Tag::JPEGInterchangeFormatLength)?;
+    exif.buf()
+        .get(offset..offset.checked_add(length)?)
+        .map(<[u8]>::to_vec)
+}
+
+#[cfg(not(feature = "exif"))]
+pub fn exif_thumbnail(_path: &Path) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(feature = "exif")]
+fn read_exif(path: &Path) -> Option<Exif> {
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::new(file);
+    Reader::new().read_from_container(&mut reader).ok()
+}
+
+#[cfg(feature = "exif")]
+fn exif_capture_time(exif: &Exif) -> Option<String> {
+    [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| {
+            let field = exif.get_field(tag, In::PRIMARY)?;
+            match &field.value {
+                Value::Ascii(values) => {
+                    let time = exif::DateTime::from_ascii(values.first()?).ok()?;
+                    (time.year > 0).then(|| {
+                        format!(
+                            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                            time.year, time.month, time.day, time.hour, time.minute, time.second
+                        )
+                    })
+                }
+                _ => None,
+            }
+        })
+}
+
+#[cfg(feature = "exif")]
+fn exif_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative: u8) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let degrees = match &field.value {
+        Value::Rational(parts) if parts.len() >= 3 => {
+            parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3_600.0
+        }
+        _ => return None,
+    };
+    if !degrees.is_finite() {
+        return None;
+    }
+
+    let sign = match exif
+        .get_field(ref_tag, In::PRIMARY)
+        .map(|field| &field.value)
+    {
+        Some(Value::Ascii(values)) if values.first().and_then(|v| v.first()) == Some(&negative) => {
+            -1.0
+        }
+        _ => 1.0,
+    };
+    Some(sign * degrees)
+}
+
+/// Formats a Unix time in the local time zone, the convention of EXIF capture
+/// times.
+pub fn format_local(secs: i64) -> Option<String> {
+    let time = DateTime::from_timestamp(secs, 0)?.with_timezone(&Local);
+    Some(time.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+pub fn date_part(taken_at: &str) -> &str {
+    taken_at.get(..10).unwrap_or(taken_at)
+}
+
+pub fn year_part(taken_at: &str) -> Option<i32> {
+    taken_at.get(..4)?.parse().ok()
+}
@@ -0,0 +1,78 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Folds text for matching, applied alike to patterns and file names:
+/// NFKC (which also maps full-width forms to ASCII), runs of Unicode
+/// whitespace to one space, full case folding and, optionally, accent
+/// stripping (é → e).
+pub fn normalize(text: &str, strip_accents: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    if text.is_ascii() {
+        push_folded(&mut out, text.chars());
+        return out;
+    }
+
+    let composed: String = if strip_accents {
+        text.nfkd()
+            .filter(|c| !is_combining_mark(*c))
+            .nfc()
+            .collect()
+    } else {
+        text.nfkc().collect()
+    };
+    push_folded(&mut out, composed.chars());
+    out
+}
+
+/// Like [`normalize`], but first splits words the way binomials appear in
+/// file names: `_`, `-`, `.` and `+` become spaces, and so does each
+/// lowercase-to-uppercase boundary (`PycnonotusSinensis`).
+pub fn normalize_words(text: &str, strip_accents: bool) -> String {
+    let composed: String = if text.is_ascii() {
+        text.to_string()
+    } else {
+        text.nfkc().collect()
+    };
+    let mut split = String::with_capacity(composed.len() + 8);
+    let mut prev_lower = false;
+    for c in composed.chars() {
+        if matches!(c, '_' | '-' | '.' | '+') {
+            split.push(' ');
+        } else {
+            if prev_lower && c.is_uppercase() {
+                split.push(' ');
+            }
+            split.push(c);
+        }
+        prev_lower = c.is_lowercase();
+    }
+    normalize(&split, strip_accents)
+}
+
+fn fold_case(out: &mut String, c: char) {
+    match c {
+        'ß' | 'ẞ' => out.push_str("ss"),
+        'ς' => out.push('σ'),
+        'ſ' => out.push('s'),
+        _ if c.is_ascii() => out.push(c.to_ascii_lowercase()),
+        _ => out.extend(c.to_lowercase()),
+    }
+}
+
+fn push_folded(out: &mut String, chars: impl Iterator<Item = char>) {
+    let mut in_space = false;
+    for c in chars {
+        if matches!(c, '\u{200B}' | '\u{FEFF}') {
+            continue;
+        }
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            fold_case(out, c);
+            in_space = false;
+        }
+    }
+}
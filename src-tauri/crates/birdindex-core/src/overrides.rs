@@ -0,0 +1,325 @@
+use crate::cache::path_string;
+use crate::types::{IocEntry, OverrideAction, OverrideFile, PhotoOverride};
+use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+const OVERRIDES_VERSION: u32 = 1;
+
+/// Manual classifications applied before name matching. Kept in the app's
+/// own data dir, so photos are never renamed.
+#[derive(Default)]
+pub struct OverrideStore {
+    overrides: Vec<PhotoOverride>,
+    by_path: HashMap<String, usize>,
+    by_content: HashMap<String, usize>,
+    content_sizes: HashSet<u64>,
+}
+
+impl OverrideStore {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Loads the store, or an empty one when the file does not exist yet.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::empty());
+        }
+
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read overrides file: {}", path.display()))?;
+        let file: OverrideFile = serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse overrides file: {}", path.display()))?;
+        Ok(Self::from_overrides(file.overrides))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create overrides dir: {}", parent.display()))?;
+        }
+
+        let file = OverrideFile {
+            version: OVERRIDES_VERSION,
+            overrides: self.overrides.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .with_context(|| format!("Failed to serialize overrides file: {}", path.display()))?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write overrides file: {}", path.display()))?;
+        Ok(())
+    }
+
+    fn from_overrides(overrides: Vec<PhotoOverride>) -> Self {
+        let mut store = Self {
+            overrides,
+            ..Self::default()
+        };
+        store.reindex();
+        store
+    }
+
+    fn reindex(&mut self) {
+        self.by_path.clear();
+        self.by_content.clear();
+        self.content_sizes.clear();
+        for (idx, photo) in self.overrides.iter().enumerate() {
+            self.by_path.insert(photo.path.clone(), idx);
+            if let Some(content) = &photo.content {
+                self.by_content.insert(content.clone(), idx);
+                if let Some(size) = content_size(content) {
+                    self.content_sizes.insert(size);
+                }
+            }
+        }
+    }
+
+    pub fn list(&self) -> &[PhotoOverride] {
+        &self.overrides
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Describes each species override whose binomial is not in the
+    /// lowercase Latin index.
+    pub fn problems(&self, latin_index: &HashMap<String, usize>) -> Vec<String> {
+        self.overrides
+            .iter()
+            .filter_map(|photo| match &photo.action {
+                OverrideAction::Species { latin } => {
+                    let (binomial, _) = split_trinomial(latin);
+                    (!latin_index.contains_key(&binomial.to_lowercase()))
+                        .then(|| format!("{}: unknown species `{latin}`", photo.path))
+                }
+                OverrideAction::Ignore => None,
+            })
+            .collect()
+    }
+
+    /// Sets the override for `path`, replacing any existing one for the same
+    /// path or content. `by_content` hashes the file so the override survives
+    /// moves and renames.
+    pub fn set(&mut self, path: &Path, action: OverrideAction, by_content: bool) -> Result<()> {
+        let content = if by_content {
+            Some(content_key(path)?)
+        } else {
+            None
+        };
+        let path = path_string(path);
+        self.overrides
+            .retain(|photo| photo.path != path && (content.is_none() || photo.content != content));
+        self.overrides.push(PhotoOverride {
+            path,
+            content,
+            action,
+        });
+        self.reindex();
+        Ok(())
+    }
+
+    /// Removes the overrides set on `path` or on the current content of the
+    /// file there. Returns whether anything was removed.
+    pub fn clear(&mut self, path: &Path) -> bool {
+        let content = if self.by_content.is_empty() {
+            None
+        } else {
+            content_key(path).ok()
+        };
+        let path = path_string(path);
+        let before = self.overrides.len();
+        self.overrides
+            .retain(|photo| photo.path != path && (content.is_none() || photo.content != content));
+        let removed = self.overrides.len() != before;
+        if removed {
+            self.reindex();
+        }
+        removed
+    }
+
+    /// Finds the override for a photo: by path first, then by content for
+    /// files whose size matches a content override. `changed` is whether the
+    /// file is new or modified since the last scan; only then is it hashed,
+    /// and a content override set on its path is checked against its content.
+    pub fn lookup(&self, path: &Path, path_str: &str, changed: bool) -> Option<&OverrideAction> {
+        if let Some(&idx) = self.by_path.get(path_str) {
+            let photo = &self.overrides[idx];
+            if photo.content.is_none() || !changed {
+                return Some(&photo.action);
+            }
+        }
+        if !changed || self.content_sizes.is_empty() {
+            return None;
+        }
+        let size = fs::metadata(path).ok()?.len();
+        if !self.content_sizes.contains(&size) {
+            return None;
+        }
+        let idx = *self.by_content.get(&content_key(path).ok()?)?;
+        Some(&self.overrides[idx].action)
+    }
+}
+
+/// Fails unless a species override names an entry of the checklist and, with
+/// a subspecies, one of that entry's subspecies.
+pub fn validate(action: &OverrideAction, entries: &[IocEntry]) -> Result<()> {
+    if let OverrideAction::Species { latin } = action {
+        let (binomial, subspecies) = split_trinomial(latin);
+        let entry = entries
+            .iter()
+            .find(|entry| entry.latin.eq_ignore_ascii_case(&binomial))
+            .ok_or_else(|| anyhow!("Not an IOC species: {latin}"))?;
+        if let Some(epithet) = subspecies {
+            if !entry.subspecies.contains(&epithet) {
+                return Err(anyhow!(
+                    "Not an IOC subspecies of {}: {epithet}",
+                    entry.latin
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits `Genus species [subspecies]` into the binomial and the lowercase
+/// subspecies epithet.
+pub fn split_trinomial(latin: &str) -> (String, Option<String>) {
+    let mut words = latin.split_whitespace();
+    let binomial = words.by_ref().take(2).collect::<Vec<_>>().join(" ");
+    (binomial, words.next().map(str::to_lowercase))
+}
+
+/// `<size>:<sha256 hex>` of a file.
+pub fn content_key(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    Ok(format!("{size}:{hex}"))
+}
+
+fn content_size(content: &str) -> Option<u64> {
+    content.split_once(':')?.0.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, OverrideStore};
+    use crate::cache::path_string;
+    use crate::types::{IocEntry, OverrideAction};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("birdindex-overrides-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn species(latin: &str) -> OverrideAction {
+        OverrideAction::Species {
+            latin: latin.to_string(),
+        }
+    }
+
+    fn lookup<'a>(
+        store: &'a OverrideStore,
+        path: &Path,
+        changed: bool,
+    ) -> Option<&'a OverrideAction> {
+        store.lookup(path, &path_string(path), changed)
+    }
+
+    #[test]
+    fn saves_set_and_cleared_overrides() {
+        let dir = temp_dir("round-trip");
+        let (a, b) = (dir.join("a.jpg"), dir.join("b.jpg"));
+        let file = dir.join("overrides.json");
+        let mut store = OverrideStore::empty();
+        store
+            .set(&a, species("Pycnonotus sinensis"), false)
+            .unwrap();
+        store.set(&b, OverrideAction::Ignore, false).unwrap();
+        store.set(&a, species("Passer montanus"), false).unwrap();
+        store.save(&file).unwrap();
+
+        let mut loaded = OverrideStore::load(&file).unwrap();
+        assert_eq!(loaded.list().len(), 2);
+        assert_eq!(lookup(&loaded, &a, true), Some(&species("Passer montanus")));
+        assert_eq!(lookup(&loaded, &b, true), Some(&OverrideAction::Ignore));
+
+        assert!(loaded.clear(&a));
+        assert!(!loaded.clear(&a));
+        loaded.save(&file).unwrap();
+        let loaded = OverrideStore::load(&file).unwrap();
+        assert_eq!(lookup(&loaded, &a, true), None);
+        assert_eq!(loaded.list().len(), 1);
+        assert!(OverrideStore::load(dir.join("missing.json"))
+            .unwrap()
+            .is_empty());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn finds_content_override_after_rename() {
+        let dir = temp_dir("content");
+        let (original, renamed) = (dir.join("DSC_0001.jpg"), dir.join("bulbul.jpg"));
+        fs::write(&original, b"photo bytes").unwrap();
+        fs::write(dir.join("other.jpg"), b"other bytes").unwrap();
+        let mut store = OverrideStore::empty();
+        store
+            .set(&original, species("Pycnonotus sinensis"), true)
+            .unwrap();
+        assert_eq!(
+            lookup(&store, &original, false),
+            Some(&species("Pycnonotus sinensis"))
+        );
+
+        fs::rename(&original, &renamed).unwrap();
+        assert_eq!(
+            lookup(&store, &renamed, true),
+            Some(&species("Pycnonotus sinensis"))
+        );
+        // Unchanged files are not hashed.
+        assert_eq!(lookup(&store, &renamed, false), None);
+        assert_eq!(lookup(&store, &dir.join("other.jpg"), true), None);
+
+        // A different file in the original place does not take the override.
+        fs::write(&original, b"new bytes").unwrap();
+        assert_eq!(lookup(&store, &original, true), None);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn validates_species_and_subspecies() {
+        let entries = [IocEntry {
+            seq: 0,
+            order: "Passeriformes".to_string(),
+            family: "Pycnonotidae".to_string(),
+            latin: "Pycnonotus sinensis".to_string(),
+            chinese: String::new(),
+            english: "Light-vented Bulbul".to_string(),
+            subspecies: vec!["sinensis".to_string(), "hainanus".to_string()],
+            other_names: Vec::new(),
+        }];
+        assert!(validate(&species("pycnonotus sinensis"), &entries).is_ok());
+        assert!(validate(&species("Pycnonotus sinensis hainanus"), &entries).is_ok());
+        assert!(validate(&species("Pycnonotus sinensis bogus"), &entries).is_err());
+        assert!(validate(&species("Passer montanus"), &entries).is_err());
+        assert!(validate(&OverrideAction::Ignore, &entries).is_ok());
+    }
+}
@@ -0,0 +1,307 @@
+use crate::cache::file_mtime;
+use crate::scanner::is_raw;
+use crate::types::PreviewResponse;
+use crate::util::fnv1a;
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const MAX_IFDS: usize = 64;
+const MAX_BOX_READ: u64 = 16 * 1024 * 1024;
+const PLACEHOLDER_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="320" height="240" viewBox="0 0 320 240"><rect width="320" height="240" fill="#e5e7eb"/><text x="160" y="126" font-family="sans-serif" font-size="20" fill="#6b7280" text-anchor="middle">RAW</text></svg>"##;
+
+pub fn resolve_preview<P: AsRef<Path>>(path: P, preview_dir: &Path) -> PreviewResponse {
+    let path = path.as_ref();
+    if !is_raw(path) {
+        return PreviewResponse {
+            path: path.to_string_lossy().to_string(),
+            placeholder: false,
+            error: None,
+        };
+    }
+
+    match cached_preview(path, preview_dir) {
+        Ok(preview) => PreviewResponse {
+            path: preview.to_string_lossy().to_string(),
+            placeholder: false,
+            error: None,
+        },
+        Err(err) => PreviewResponse {
+            path: placeholder_path(preview_dir)
+                .map(|placeholder| placeholder.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            placeholder: true,
+            error: Some(format!("{err:#}")),
+        },
+    }
+}
+
+fn cached_preview(path: &Path, preview_dir: &Path) -> Result<PathBuf> {
+    let key = format!("{}:{}", path.to_string_lossy(), file_mtime(path));
+    let target = preview_dir.join(format!("{:016x}.jpg", fnv1a(&key)));
+    if target.exists() {
+        return Ok(target);
+    }
+
+    let jpeg = extract_embedded_jpeg(path)?;
+    fs::create_dir_all(preview_dir)
+        .with_context(|| format!("Failed to create preview dir: {}", preview_dir.display()))?;
+    fs::write(&target, jpeg)
+        .with_context(|| format!("Failed to write preview: {}", target.display()))?;
+    Ok(target)
+}
+
+fn placeholder_path(preview_dir: &Path) -> Result<PathBuf> {
+    let target = preview_dir.join("placeholder.svg");
+    if !target.exists() {
+        fs::create_dir_all(preview_dir)?;
+        fs::write(&target, PLACEHOLDER_SVG)?;
+    }
+    Ok(target)
+}
+
+pub fn extract_embedded_jpeg<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open RAW file: {}", path.display()))?;
+
+    let mut magic = [0u8; 16];
+    let read = file.read(&mut magic)?;
+    let magic = &magic[..read];
+
+    let jpeg = if magic.starts_with(b"FUJIFILMCCD-RAW") {
+        raf_preview(&mut file)?
+    } else if magic.len() >= 8 && &magic[4..8] == b"ftyp" {
+        cr3_preview(&mut file)?
+    } else if magic.starts_with(b"II") || magic.starts_with(b"MM") {
+        tiff_preview(&mut file)?
+    } else {
+        None
+    };
+
+    jpeg.ok_or_else(|| anyhow!("No embedded JPEG preview in {}", path.display()))
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![0u8; len];
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn is_jpeg(data: &[u8]) -> bool {
+    data.len() > 4 && data[0] == 0xFF && data[1] == 0xD8
+}
+
+fn raf_preview(file: &mut File) -> Result<Option<Vec<u8>>> {
+    let header = read_at(file, 84, 8)?;
+    let offset = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+    let length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let file_len = file.metadata()?.len();
+    if offset == 0 || length == 0 || offset + length as u64 > file_len {
+        return Ok(None);
+    }
+    let data = read_at(file, offset, length)?;
+    Ok(is_jpeg(&data).then_some(data))
+}
+
+#[derive(Clone, Copy)]
+struct ByteOrder {
+    little: bool,
+}
+
+impl ByteOrder {
+    fn u16(self, bytes: &[u8]) -> u16 {
+        let raw = [bytes[0], bytes[1]];
+        if self.little {
+            u16::from_le_bytes(raw)
+        } else {
+            u16::from_be_bytes(raw)
+        }
+    }
+
+    fn u32(self, bytes: &[u8]) -> u32 {
+        let raw = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.little {
+            u32::from_le_bytes(raw)
+        } else {
+            u32::from_be_bytes(raw)
+        }
+    }
+}
+
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    value: [u8; 4],
+}
+
+impl IfdEntry {
+    fn first(&self, order: ByteOrder) -> u32 {
+        match self.kind {
+            3 => order.u16(&self.value) as u32,
+            _ => order.u32(&self.value),
+        }
+    }
+}
+
+fn tiff_preview(file: &mut File) -> Result<Option<Vec<u8>>> {
+    let header = read_at(file, 0, 8)?;
+    let order = ByteOrder {
+        little: &header[..2] == b"II",
+    };
+    let file_len = file.metadata()?.len();
+
+    let mut pending = vec![order.u32(&header[4..8]) as u64];
+    let mut visited = HashSet::new();
+    let mut best: Option<Vec<u8>> = None;
+
+    while let Some(offset) = pending.pop() {
+        if offset == 0 || offset >= file_len || visited.len() >= MAX_IFDS {
+            continue;
+        }
+        if !visited.insert(offset) {
+            continue;
+        }
+
+        let entries = match read_ifd(file, order, offset) {
+            Ok((entries, next)) => {
+                pending.push(next as u64);
+                entries
+            }
+            Err(_) => continue,
+        };
+
+        let find = |tag: u16| entries.iter().find(|entry| entry.tag == tag);
+
+        if let Some(sub_ifds) = find(0x014A) {
+            pending.extend(ifd_offsets(file, order, sub_ifds).unwrap_or_default());
+        }
+        if let Some(exif) = find(0x8769) {
+            pending.push(exif.first(order) as u64);
+        }
+
+        let mut candidate = None;
+        if let (Some(start), Some(length)) = (find(0x0201), find(0x0202)) {
+            candidate = Some((start.first(order) as u64, length.first(order) as u64));
+        } else if let (Some(compression), Some(start), Some(length)) =
+            (find(0x0103), find(0x0111), find(0x0117))
+        {
+            let compression = compression.first(order);
+            let reduced = find(0x00FE)
+                .map(|kind| kind.first(order) == 1)
+                .unwrap_or(false);
+            if start.count == 1 && (compression == 6 || (compression == 7 && reduced)) {
+                candidate = Some((start.first(order) as u64, length.first(order) as u64));
+            }
+        }
+
+        if let Some((start, length)) = candidate {
+            let current = best.as_ref().map(|data| data.len() as u64).unwrap_or(0);
+            if length > current && start + length <= file_len {
+                // A bad entry only rules out this candidate.
+                match read_at(file, start, length as usize) {
+                    Ok(data) if is_jpeg(&data) => best = Some(data),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(best)
+}
+
+fn read_ifd(file: &mut File, order: ByteOrder, offset: u64) -> Result<(Vec<IfdEntry>, u32)> {
+    let count = order.u16(&read_at(file, offset, 2)?) as usize;
+    let raw = read_at(file, offset + 2, count * 12 + 4)?;
+    let entries = raw
+        .chunks_exact(12)
+        .map(|chunk| IfdEntry {
+            tag: order.u16(&chunk[0..2]),
+            kind: order.u16(&chunk[2..4]),
+            count: order.u32(&chunk[4..8]),
+            value: [chunk[8], chunk[9], chunk[10], chunk[11]],
+        })
+        .collect();
+    let next = order.u32(&raw[count * 12..]);
+    Ok((entries, next))
+}
+
+fn ifd_offsets(file: &mut File, order: ByteOrder, entry: &IfdEntry) -> Result<Vec<u64>> {
+    if entry.count <= 1 {
+        return Ok(vec![entry.first(order) as u64]);
+    }
+    let count = entry.count.min(MAX_IFDS as u32) as usize;
+    let raw = read_at(file, order.u32(&entry.value) as u64, count * 4)?;
+    Ok(raw
+        .chunks_exact(4)
+        .map(|chunk| order.u32(chunk) as u64)
+        .collect())
+}
+
+fn cr3_preview(file: &mut File) -> Result<Option<Vec<u8>>> {
+    let file_len = file.metadata()?.len();
+    let mut offset = 0u64;
+    let mut thumbnail = None;
+
+    while offset + 8 <= file_len {
+        let header = read_at(file, offset, 8)?;
+        let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let kind = [header[4], header[5], header[6], header[7]];
+        if size == 1 {
+            let large = read_at(file, offset + 8, 8)?;
+            size = u64::from_be_bytes([
+                large[0], large[1], large[2], large[3], large[4], large[5], large[6], large[7],
+            ]);
+        } else if size == 0 {
+            size = file_len - offset;
+        }
+        if size < 8 {
+            break;
+        }
+
+        if &kind != b"mdat" && size <= MAX_BOX_READ {
+            let data = read_at(file, offset, size as usize)?;
+            if let Some(preview) = find_boxed_jpeg(&data, b"PRVW") {
+                return Ok(Some(preview));
+            }
+            if thumbnail.is_none() {
+                thumbnail = find_boxed_jpeg(&data, b"THMB");
+            }
+        }
+
+        // A box running past the end of the file is the last one.
+        let Some(next) = offset.checked_add(size).filter(|&next| next <= file_len) else {
+            break;
+        };
+        offset = next;
+    }
+
+    Ok(thumbnail)
+}
+
+fn find_boxed_jpeg(data: &[u8], kind: &[u8; 4]) -> Option<Vec<u8>> {
+    let tag = data.windows(4).position(|window| window == kind)?;
+    if tag < 4 {
+        return None;
+    }
+    let start = tag - 4;
+    let size = u32::from_be_bytes([
+        data[start],
+        data[start + 1],
+        data[start + 2],
+        data[start + 3],
+    ]) as usize;
+    let end = (start + size).min(data.len());
+    let body = data.get(tag + 4..end)?;
+    let soi = body
+        .windows(2)
+        .take(32)
+        .position(|window| window == [0xFF, 0xD8])?;
+    let jpeg = &body[soi..];
+    let eoi = jpeg.windows(2).rposition(|window| window == [0xFF, 0xD9])?;
+    Some(jpeg[..eoi + 2].to_vec())
+}
@@ -0,0 +1,404 @@
+use crate::cache::{file_mtime, path_string, CacheIndex};
+use crate::matcher::{NameMatch, NameMatcher};
+use crate::metadata::{read_metadata, PhotoMetadata};
+use crate::overrides::{split_trinomial, OverrideStore};
+use crate::template::FileTemplates;
+use crate::tree::genus_name;
+use crate::types::{
+    CacheEntry, DateSource, Identification, IocEntry, MatchSource, MatchedPhoto, OverrideAction,
+    ScanStats, VetoedMatch,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+pub const RAW_EXTENSIONS: [&str; 12] = [
+    "arw", "cr2", "cr3", "dng", "nef", "nrw", "orf", "pef", "raf", "rw2", "sr2", "srf",
+];
+
+#[derive(Debug)]
+pub struct ScanOutput {
+    pub matches: Vec<MatchedPhoto>,
+    pub unmatched: Vec<String>,
+    pub vetoed: Vec<VetoedMatch>,
+    pub cache_entries: Vec<CacheEntry>,
+    pub stats: ScanStats,
+}
+
+/// Walks `roots` in parallel, matching supported photos and reusing cached
+/// results, which keep the confidence they were scored with. Overrides are
+/// applied before either and score 100. A matching file name template
+/// narrows matching to its species segment and adds its date, location and
+/// sequence. The nearest folder below the root whose name matches is a
+/// second source for the file's hit.
+pub fn scan_paths(
+    roots: &[String],
+    entries: &[IocEntry],
+    latin_index: &HashMap<String, usize>,
+    matcher: &NameMatcher,
+    cache: &CacheIndex,
+    overrides: &OverrideStore,
+    templates: &FileTemplates,
+) -> ScanOutput {
+    let exts: HashSet<&'static str> = ["jpg", "jpeg", "png", "heic"]
+        .into_iter()
+        .chain(RAW_EXTENSIONS)
+        .collect();
+
+    // Hits of folder names, shared by the files inside.
+    let folder_hits: Mutex<HashMap<PathBuf, Option<NameMatch>>> = Mutex::new(HashMap::new());
+    let folder_hit = |dir: &Path| {
+        if let Some(hit) = folder_hits.lock().ok()?.get(dir) {
+            return hit.clone();
+        }
+        let hit = dir
+            .file_name()
+            .and_then(|name| matcher.match_file(&name.to_string_lossy()));
+        if let Ok(mut hits) = folder_hits.lock() {
+            hits.insert(dir.to_path_buf(), hit.clone());
+        }
+        hit
+    };
+
+    let walker = roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).follow_links(false).into_iter());
+
+    let results: Vec<ScanItem> = walker
+        .par_bridge()
+        .filter_map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => return None,
+            };
+
+            if !entry.file_type().is_file() {
+                return None;
+            }
+
+            let path = entry.path();
+            if !is_supported(path, &exts) {
+                return None;
+            }
+
+            let file_name = match path.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => return None,
+            };
+
+            let file_stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_name.clone());
+
+            let mtime = file_mtime(path);
+            let path_str = path_string(path);
+            let mut cached = cache.get(&path_str).filter(|cached| cached.mtime == mtime);
+            let fields = templates.extract(&file_stem).unwrap_or_default();
+
+            // Content overrides are only hashed for on new or modified files
+            // and on files that were overridden before.
+            let changed = cached.is_none_or(|cached| cached.source == MatchSource::Override);
+            match overrides.lookup(path, &path_str, changed) {
+                Some(OverrideAction::Ignore) => return Some(ScanItem::ignored(cached.cloned())),
+                Some(OverrideAction::Species { latin }) => {
+                    let (binomial, subspecies) = split_trinomial(latin);
+                    if let Some(&species_idx) = latin_index.get(&binomial.to_lowercase()) {
+                        let cache_entry = match cached {
+                            Some(cached) => cached.clone(),
+                            None => metadata_entry(
+                                path_str.clone(),
+                                mtime,
+                                read_metadata(path, fields.date),
+                            ),
+                        };
+                        let photo = MatchedPhoto {
+                            path: path_str,
+                            file_name,
+                            species_idx,
+                            identification: Identification::Species,
+                            subspecies,
+                            taken_at: cache_entry.taken_at.clone(),
+                            date_source: cache_entry.date_source,
+                            latitude: cache_entry.latitude,
+                            longitude: cache_entry.longitude,
+                            location: fields.location,
+                            sequence: fields.sequence,
+                            source: MatchSource::Override,
+                            confidence: 100,
+                        };
+                        return Some(ScanItem {
+                            matched_photo: Some(photo),
+                            cache_entry: Some(cache_entry),
+                            ignored: false,
+                        });
+                    }
+                }
+                None => {}
+            }
+
+            // Only the metadata of a file that was overridden before.
+            let cached_metadata = cached
+                .filter(|cached| cached.source == MatchSource::Override)
+                .map(|cached| PhotoMetadata {
+                    taken_at: cached.taken_at.clone(),
+                    date_source: cached.date_source,
+                    latitude: cached.latitude,
+                    longitude: cached.longitude,
+                });
+            if cached_metadata.is_some() {
+                cached = None;
+            }
+
+            if let Some(cached) = cached {
+                let hit = match (&cached.species_latin, &cached.genus) {
+                    (Some(latin), _) => {
+                        let species_idx = latin_index.get(&latin.to_lowercase()).copied();
+                        match &cached.combined_with {
+                            Some((kind, other)) => species_idx
+                                .zip(latin_index.get(&other.to_lowercase()).copied())
+                                .map(|(idx, other)| {
+                                    (idx, Identification::Combination { kind: *kind, other })
+                                }),
+                            None => species_idx.map(|idx| (idx, Identification::Species)),
+                        }
+                    }
+                    (None, Some(genus)) => matcher
+                        .genus_entry(genus)
+                        .map(|idx| (idx, Identification::Genus)),
+                    (None, None) => {
+                        return Some(ScanItem::unmatched(CacheEntry {
+                            path: cached.path.clone(),
+                            mtime,
+                            species_latin: None,
+                            subspecies: None,
+                            genus: None,
+                            combined_with: None,
+                            vetoed: cached.vetoed.clone(),
+                            source: MatchSource::default(),
+                            confidence: 0,
+                            taken_at: None,
+                            date_source: DateSource::default(),
+                            latitude: None,
+                            longitude: None,
+                        }));
+                    }
+                };
+                if let Some((species_idx, identification)) = hit {
+                    return Some(ScanItem::matched(
+                        MatchedPhoto {
+                            path: path_str,
+                            file_name,
+                            species_idx,
+                            identification,
+                            subspecies: cached.subspecies.clone(),
+                            taken_at: cached.taken_at.clone(),
+                            date_source: cached.date_source,
+                            latitude: cached.latitude,
+                            longitude: cached.longitude,
+                            location: fields.location,
+                            sequence: fields.sequence,
+                            source: cached.source,
+                            confidence: cached.confidence,
+                        },
+                        cached.clone(),
+                    ));
+                }
+            }
+
+            let folder = path
+                .ancestors()
+                .skip(1)
+                .take(entry.depth().saturating_sub(1))
+                .find_map(folder_hit);
+            let file_name_part = fields.species.as_deref().unwrap_or(&file_stem);
+            match matcher.classify_in_folder(file_name_part, folder.as_ref()) {
+                Ok(Some(hit)) => {
+                    let metadata =
+                        cached_metadata.unwrap_or_else(|| read_metadata(path, fields.date));
+                    let latin = &entries[hit.species_idx].latin;
+                    Some(ScanItem::matched(
+                        MatchedPhoto {
+                            path: path_str.clone(),
+                            file_name,
+                            species_idx: hit.species_idx,
+                            identification: hit.identification,
+                            subspecies: hit.subspecies.clone(),
+                            taken_at: metadata.taken_at.clone(),
+                            date_source: metadata.date_source,
+                            latitude: metadata.latitude,
+                            longitude: metadata.longitude,
+                            location: fields.location,
+                            sequence: fields.sequence,
+                            source: hit.source,
+                            confidence: hit.confidence,
+                        },
+                        CacheEntry {
+                            path: path_str,
+                            mtime,
+                            species_latin: (hit.identification != Identification::Genus)
+                                .then(|| latin.clone()),
+                            subspecies: hit.subspecies,
+                            genus: (hit.identification == Identification::Genus)
+                                .then(|| genus_name(latin)),
+                            combined_with: match hit.identification {
+                                Identification::Combination { kind, other } => {
+                                    Some((kind, entries[other].latin.clone()))
+                                }
+                                _ => None,
+                            },
+                            vetoed: None,
+                            source: hit.source,
+                            confidence: hit.confidence,
+                            taken_at: metadata.taken_at,
+                            date_source: metadata.date_source,
+                            latitude: metadata.latitude,
+                            longitude: metadata.longitude,
+                        },
+                    ))
+                }
+                outcome => Some(ScanItem::unmatched(CacheEntry {
+                    path: path_str,
+                    mtime,
+                    species_latin: None,
+                    subspecies: None,
+                    genus: None,
+                    combined_with: None,
+                    vetoed: outcome
+                        .err()
+                        .map(|veto| (entries[veto.species_idx].latin.clone(), veto.reason)),
+                    source: MatchSource::default(),
+                    confidence: 0,
+                    taken_at: None,
+                    date_source: DateSource::default(),
+                    latitude: None,
+                    longitude: None,
+                })),
+            }
+        })
+        .collect();
+
+    let mut matches = Vec::new();
+    let mut unmatched = Vec::new();
+    let mut vetoed = Vec::new();
+    let mut cache_entries = Vec::with_capacity(results.len());
+    let mut total_files = 0usize;
+    let mut matched_files = 0usize;
+    let mut ignored_files = 0usize;
+
+    for item in results {
+        total_files += 1;
+        match item.matched_photo {
+            Some(photo) => {
+                matched_files += 1;
+                matches.push(photo);
+            }
+            None if item.ignored => ignored_files += 1,
+            None => {
+                if let Some(entry) = &item.cache_entry {
+                    unmatched.push(entry.path.clone());
+                    if let Some((latin, reason)) = &entry.vetoed {
+                        vetoed.push(VetoedMatch {
+                            path: entry.path.clone(),
+                            latin: latin.clone(),
+                            reason: reason.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        cache_entries.extend(item.cache_entry);
+    }
+
+    let unmatched_files = total_files.saturating_sub(matched_files + ignored_files);
+
+    ScanOutput {
+        matches,
+        unmatched,
+        stats: ScanStats {
+            total_files,
+            matched_files,
+            unmatched_files,
+            ignored_files,
+            vetoed_files: vetoed.len(),
+        },
+        vetoed,
+        cache_entries,
+    }
+}
+
+pub fn is_raw(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            let ext = ext.to_ascii_lowercase();
+            RAW_EXTENSIONS.contains(&ext.as_str())
+        })
+        .unwrap_or(false)
+}
+
+/// A cache entry holding only the metadata of an overridden file, so a later
+/// scan neither rereads it nor replays a match that was never made.
+fn metadata_entry(path: String, mtime: i64, metadata: PhotoMetadata) -> CacheEntry {
+    CacheEntry {
+        path,
+        mtime,
+        species_latin: None,
+        subspecies: None,
+        genus: None,
+        combined_with: None,
+        vetoed: None,
+        source: MatchSource::Override,
+        confidence: 0,
+        taken_at: metadata.taken_at,
+        date_source: metadata.date_source,
+        latitude: metadata.latitude,
+        longitude: metadata.longitude,
+    }
+}
+
+fn is_supported(path: &Path, exts: &HashSet<&'static str>) -> bool {
+    let ext = path.extension().and_then(|ext| ext.to_str());
+    match ext {
+        Some(ext) => {
+            let ext = ext.to_ascii_lowercase();
+            exts.contains(ext.as_str())
+        }
+        None => false,
+    }
+}
+
+struct ScanItem {
+    matched_photo: Option<MatchedPhoto>,
+    cache_entry: Option<CacheEntry>,
+    ignored: bool,
+}
+
+impl ScanItem {
+    fn matched(matched_photo: MatchedPhoto, cache_entry: CacheEntry) -> Self {
+        Self {
+            matched_photo: Some(matched_photo),
+            cache_entry: Some(cache_entry),
+            ignored: false,
+        }
+    }
+
+    fn unmatched(cache_entry: CacheEntry) -> Self {
+        Self {
+            matched_photo: None,
+            cache_entry: Some(cache_entry),
+            ignored: false,
+        }
+    }
+
+    fn ignored(cache_entry: Option<CacheEntry>) -> Self {
+        Self {
+            matched_photo: None,
+            cache_entry,
+            ignored: true,
+        }
+    }
+}
@@ -0,0 +1,500 @@
+use crate::chinese::chinese_forms;
+use crate::normalize::normalize;
+use crate::tree::unidentified_name;
+use crate::types::{CombinationNode, IocEntry, SearchField, SearchMatch, TaxonTree};
+use aho_corasick::AhoCorasick;
+use std::collections::HashMap;
+
+pub const DEFAULT_LIMIT: usize = 50;
+const MIN_FUZZY_CHARS: usize = 3;
+const MAX_FUZZY_CHARS: usize = 64;
+
+/// Name and path index behind [`crate::session::ScanSession::search`].
+pub struct SearchIndex {
+    names: Vec<NameKey>,
+    name_text: String,
+    photo_text: String,
+    photos: Vec<PhotoKey>,
+    /// Tree paths of the nodes holding photos, see `photo_location`.
+    locations: Vec<Vec<String>>,
+}
+
+struct NameKey {
+    entry: usize,
+    field: SearchField,
+    display: String,
+    prefix_only: bool,
+    /// Span of the normalized name in `SearchIndex::name_text`.
+    start: usize,
+    end: usize,
+    /// See `char_mask` and `char_counts`.
+    mask: u64,
+    counts: u64,
+    chars: Vec<char>,
+    words: Vec<Vec<char>>,
+}
+
+struct PhotoKey {
+    path: String,
+    name_start: usize,
+    end: usize,
+    entry: Option<usize>,
+    location: Option<usize>,
+}
+
+pub enum Target {
+    Species { entry: usize, key: usize },
+    Photo { index: usize, entry: Option<usize> },
+}
+
+pub struct Candidate {
+    pub matched: SearchMatch,
+    pub field: SearchField,
+    pub distance: usize,
+    pub target: Target,
+}
+
+impl SearchIndex {
+    pub fn new(entries: &[IocEntry], tree: &TaxonTree, unmatched: &[String]) -> Self {
+        let mut index = Self {
+            names: Vec::with_capacity(entries.len() * 6),
+            name_text: String::new(),
+            photo_text: String::new(),
+            photos: Vec::new(),
+            locations: Vec::new(),
+        };
+        for (idx, entry) in entries.iter().enumerate() {
+            for (field, text) in [
+                (SearchField::Latin, &entry.latin),
+                (SearchField::English, &entry.english),
+            ]
+            .into_iter()
+            .chain(
+                entry
+                    .other_names
+                    .iter()
+                    .map(|name| (SearchField::OtherLanguage, name)),
+            ) {
+                index.push_name(idx, field, text, false);
+            }
+            index.push_chinese(idx, SearchField::Chinese, &entry.chinese);
+        }
+
+        let entry_index: HashMap<String, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.latin.to_lowercase(), idx))
+            .collect();
+
+        index.push_combinations(&tree.combinations, &[]);
+        for order in &tree.orders {
+            let path = [order.name.clone()];
+            index.push_combinations(&order.combinations, &path);
+            for family in &order.families {
+                let path = [order.name.clone(), family.name.clone()];
+                index.push_combinations(&family.combinations, &path);
+                for genus in &family.genera {
+                    let path = [order.name.clone(), family.name.clone(), genus.name.clone()];
+                    index.push_combinations(&genus.combinations, &path);
+                    if !genus.unidentified_photos.is_empty() {
+                        let location = index.push_location(&path, unidentified_name(&genus.name));
+                        for photo in &genus.unidentified_photos {
+                            index.push_photo(&photo.path, None, Some(location));
+                        }
+                    }
+                    for species in &genus.species {
+                        let entry = entry_index.get(&species.latin.to_lowercase()).copied();
+                        let location = index.push_location(&path, species.latin.clone());
+                        for photo in &species.photos {
+                            index.push_photo(&photo.path, entry, Some(location));
+                        }
+                    }
+                }
+            }
+        }
+        for path in unmatched {
+            index.push_photo(path, None, None);
+        }
+        index
+    }
+
+    /// Adds user aliases, with the Traditional and pinyin forms of Chinese
+    /// ones, as names of their species.
+    pub fn add_aliases(&mut self, aliases: &[(String, usize)]) {
+        for (alias, entry) in aliases {
+            self.push_chinese(*entry, SearchField::Alias, alias);
+        }
+    }
+
+    /// Adds a name with its Traditional, pinyin and pinyin-initials forms; the
+    /// initials only match as a prefix.
+    fn push_chinese(&mut self, entry: usize, field: SearchField, name: &str) {
+        let forms = chinese_forms(name);
+        for (field, text, prefix_only) in [
+            (field, name, false),
+            (SearchField::Traditional, forms.traditional.as_str(), false),
+            (SearchField::Pinyin, forms.pinyin.as_str(), false),
+            (SearchField::Pinyin, forms.initials.as_str(), true),
+        ] {
+            self.push_name(entry, field, text, prefix_only);
+        }
+    }
+
+    fn push_name(&mut self, entry: usize, field: SearchField, display: &str, prefix_only: bool) {
+        if display.is_empty() {
+            return;
+        }
+        let text = normalize(display, true);
+        let start = self.name_text.len();
+        self.name_text.push_str(&text);
+        self.name_text.push('\n');
+        self.names.push(NameKey {
+            entry,
+            field,
+            display: display.to_string(),
+            prefix_only,
+            start,
+            end: start + text.len(),
+            mask: char_mask(text.chars()),
+            counts: char_counts(text.chars()),
+            chars: text.chars().collect(),
+            words: text
+                .split([' ', '-'])
+                .filter(|word| !word.is_empty())
+                .map(|word| word.chars().collect())
+                .collect(),
+        });
+    }
+
+    /// Hybrids and pairs are listed on the lowest rank both species share,
+    /// `parent`.
+    fn push_combinations(&mut self, combinations: &[CombinationNode], parent: &[String]) {
+        for combination in combinations {
+            let location = self.push_location(parent, combination.name.clone());
+            for photo in &combination.photos {
+                self.push_photo(&photo.path, None, Some(location));
+            }
+        }
+    }
+
+    fn push_location(&mut self, parent: &[String], name: String) -> usize {
+        let mut path = parent.to_vec();
+        path.push(name);
+        self.locations.push(path);
+        self.locations.len() - 1
+    }
+
+    fn push_photo(&mut self, path: &str, entry: Option<usize>, location: Option<usize>) {
+        let start = self.photo_text.len();
+        self.photo_text.push_str(&normalize(path, true));
+        let end = self.photo_text.len();
+        let name_start = self.photo_text[start..end]
+            .rfind(['/', '\\'])
+            .map(|pos| start + pos + 1)
+            .unwrap_or(start);
+        self.photo_text.push('\n');
+        self.photos.push(PhotoKey {
+            path: path.to_string(),
+            name_start,
+            end,
+            entry,
+            location,
+        });
+    }
+
+    /// Returns unsorted candidates: every matching species field and up to
+    /// `limit` photos per match kind.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Candidate> {
+        let query = normalize(query, true);
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let photos = self.search_photos(query, limit);
+        // Fuzzy species hits rank below every other hit, so they are only
+        // looked for while the others leave room.
+        let mut candidates = self.search_names(query, limit.saturating_sub(photos.len()));
+        candidates.extend(photos);
+        candidates
+    }
+
+    fn search_names(&self, query: &str, fuzzy_room: usize) -> Vec<Candidate> {
+        let mut best: HashMap<usize, Candidate> = HashMap::new();
+
+        // Exact, prefix and substring matches, in one pass over all names.
+        let Ok(finder) = AhoCorasick::new([query]) else {
+            return Vec::new();
+        };
+        for found in finder.find_iter(&self.name_text) {
+            let key_idx = self.names.partition_point(|key| key.end < found.start());
+            let key = &self.names[key_idx];
+            let before = &self.name_text[key.start..found.start()];
+            let (matched, distance) = if before.is_empty() && found.end() == key.end {
+                (SearchMatch::Exact, 0)
+            } else if before.is_empty() {
+                (SearchMatch::Prefix, 0)
+            } else if key.prefix_only {
+                continue;
+            } else if before.ends_with([' ', '-']) {
+                (SearchMatch::Prefix, 1)
+            } else {
+                (SearchMatch::Substring, 0)
+            };
+            keep_best(&mut best, key_idx, key, matched, distance);
+        }
+
+        let query_chars: Vec<char> = query.chars().collect();
+        let max_typos = match query_chars.len() {
+            len if len < MIN_FUZZY_CHARS => 0,
+            len if len <= 5 => 1,
+            _ => 2,
+        };
+        if max_typos == 0 || best.len() >= fuzzy_room {
+            return best.into_values().collect();
+        }
+
+        // Each typo loses at most one query character, so names missing more
+        // of them are skipped before computing distances.
+        let query_mask = char_mask(query_chars.iter().copied());
+        let query_counts = char_counts(query_chars.iter().copied());
+        for (key_idx, key) in self.names.iter().enumerate() {
+            if key.prefix_only
+                || (query_mask & !key.mask).count_ones() as usize > max_typos
+                || missing_chars(query_counts, key.counts) > max_typos
+            {
+                continue;
+            }
+            if best
+                .get(&key.entry)
+                .is_some_and(|current| current.matched < SearchMatch::Fuzzy)
+            {
+                continue;
+            }
+            if let Some(distance) = fuzzy_distance(key, &query_chars, max_typos) {
+                keep_best(&mut best, key_idx, key, SearchMatch::Fuzzy, distance);
+            }
+        }
+        best.into_values().collect()
+    }
+
+    fn search_photos(&self, query: &str, limit: usize) -> Vec<Candidate> {
+        let mut tiers: [Vec<Candidate>; 3] = Default::default();
+        let mut last = None;
+        let Ok(finder) = AhoCorasick::new([query]) else {
+            return Vec::new();
+        };
+        for found in finder.find_iter(&self.photo_text) {
+            let offset = found.start();
+            let index = self.photos.partition_point(|photo| photo.end < offset);
+            if last == Some(index) {
+                continue;
+            }
+            last = Some(index);
+
+            let photo = &self.photos[index];
+            let name = &self.photo_text[photo.name_start..photo.end];
+            let (tier, matched) = if name == query {
+                (0, SearchMatch::Exact)
+            } else if name.starts_with(query) {
+                (1, SearchMatch::Prefix)
+            } else {
+                (2, SearchMatch::Substring)
+            };
+            if tiers[tier].len() < limit {
+                tiers[tier].push(Candidate {
+                    matched,
+                    field: SearchField::Path,
+                    distance: 0,
+                    target: Target::Photo {
+                        index,
+                        entry: photo.entry,
+                    },
+                });
+            } else if tiers.iter().all(|tier| tier.len() >= limit) {
+                break;
+            }
+        }
+        tiers.into_iter().flatten().collect()
+    }
+
+    pub fn name_text(&self, key: usize) -> &str {
+        &self.names[key].display
+    }
+
+    pub fn photo_path(&self, index: usize) -> &str {
+        &self.photos[index].path
+    }
+
+    /// The tree path of the species, genus-only or combination node listing a
+    /// photo; empty for unmatched photos.
+    pub fn photo_location(&self, index: usize) -> &[String] {
+        self.photos[index]
+            .location
+            .map_or(&[], |location| &self.locations[location])
+    }
+}
+
+/// Keeps the best match per species.
+fn keep_best(
+    best: &mut HashMap<usize, Candidate>,
+    key_idx: usize,
+    key: &NameKey,
+    matched: SearchMatch,
+    distance: usize,
+) {
+    let better = best
+        .get(&key.entry)
+        .is_none_or(|current| (matched, distance) < (current.matched, current.distance));
+    if better {
+        best.insert(
+            key.entry,
+            Candidate {
+                matched,
+                field: key.field,
+                distance,
+                target: Target::Species {
+                    entry: key.entry,
+                    key: key_idx,
+                },
+            },
+        );
+    }
+}
+
+/// The smallest distance from the query to the whole name, its prefix of the
+/// query's length or one of its words.
+fn fuzzy_distance(key: &NameKey, query_chars: &[char], max_typos: usize) -> Option<usize> {
+    let prefix = key.chars.get(..query_chars.len()).unwrap_or(&key.chars);
+    std::iter::once(&key.chars[..])
+        .chain(std::iter::once(prefix))
+        .chain(key.words.iter().map(Vec::as_slice))
+        .filter_map(|candidate| edit_distance(query_chars, candidate, max_typos))
+        .min()
+}
+
+/// One bit per character, folded into 64 buckets.
+fn char_mask(chars: impl Iterator<Item = char>) -> u64 {
+    chars.fold(0, |mask, ch| mask | 1 << (u32::from(ch) % 64))
+}
+
+/// Character counts folded into 16 four-bit buckets; a full bucket stands
+/// for any larger count.
+fn char_counts(chars: impl Iterator<Item = char>) -> u64 {
+    let mut counts = 0u64;
+    for ch in chars {
+        let shift = u32::from(ch) % 16 * 4;
+        if (counts >> shift) & 0xf < 0xf {
+            counts += 1 << shift;
+        }
+    }
+    counts
+}
+
+/// How many query characters a name cannot contain, a lower bound on the
+/// edit distance to the name and to any part of it.
+fn missing_chars(query: u64, name: u64) -> usize {
+    (0..16)
+        .map(|bucket| {
+            let wanted = (query >> (bucket * 4)) & 0xf;
+            let present = (name >> (bucket * 4)) & 0xf;
+            if present == 0xf {
+                0
+            } else {
+                wanted.saturating_sub(present) as usize
+            }
+        })
+        .sum()
+}
+
+/// Levenshtein distance, or `None` once it exceeds `max`. Names longer than
+/// `MAX_FUZZY_CHARS` are only matched exactly, by prefix or by substring.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max || b.len() >= MAX_FUZZY_CHARS {
+        return None;
+    }
+    let mut prev = [0usize; MAX_FUZZY_CHARS];
+    let mut row = [0usize; MAX_FUZZY_CHARS];
+    for (j, cell) in prev.iter_mut().enumerate().take(b.len() + 1) {
+        *cell = j;
+    }
+    for (i, ca) in a.iter().enumerate() {
+        row[0] = i + 1;
+        let mut row_min = row[0];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+            row_min = row_min.min(row[j + 1]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{char_counts, char_mask, edit_distance, missing_chars};
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    /// Characters the query has but the name lacks, as the prefilter counts them.
+    fn missing(query: &str, name: &str) -> usize {
+        let masked = (char_mask(query.chars()) & !char_mask(name.chars())).count_ones() as usize;
+        masked.max(missing_chars(
+            char_counts(query.chars()),
+            char_counts(name.chars()),
+        ))
+    }
+
+    #[test]
+    fn measures_edit_distance_up_to_a_limit() {
+        assert_eq!(
+            edit_distance(&chars("mallad"), &chars("mallard"), 2),
+            Some(1)
+        );
+        assert_eq!(
+            edit_distance(&chars("sinensus"), &chars("sinensis"), 1),
+            Some(1)
+        );
+        assert_eq!(
+            edit_distance(&chars("bulbul"), &chars("bulbul"), 0),
+            Some(0)
+        );
+        assert_eq!(edit_distance(&chars("warbler"), &chars("bulbul"), 2), None);
+        assert_eq!(edit_distance(&chars("ab"), &chars("abcd"), 1), None);
+        assert_eq!(
+            edit_distance(&chars("a"), &chars(&"a".repeat(64)), 64),
+            None
+        );
+    }
+
+    #[test]
+    fn prefilter_never_skips_a_close_name() {
+        for (query, name) in [
+            ("mallad", "mallard"),
+            ("malard", "mallard"),
+            ("sinensus", "sinensis"),
+            ("pycnonotsu", "pycnonotus"),
+            ("baitu", "baitou"),
+            ("白头", "白头鹎"),
+            ("aaaaaaaaaaaaaaaaab", "aaaaaaaaaaaaaaaaaa"),
+        ] {
+            let distance = edit_distance(&chars(query), &chars(name), 2).unwrap();
+            assert!(missing(query, name) <= distance, "{query} / {name}");
+        }
+    }
+
+    #[test]
+    fn prefilter_skips_names_missing_query_characters() {
+        assert_eq!(missing("warbler", "bulbul"), 4);
+        // Counts catch repeated letters the mask alone cannot.
+        assert_eq!(missing("llll", "la"), 3);
+        assert_eq!(missing("bulbul", "light-vented bulbul"), 0);
+    }
+}
@@ -0,0 +1,812 @@
+use crate::aliases::AliasDictionary;
+use crate::search::{SearchIndex, Target};
+use crate::tree::{genus_name, unidentified_genus, unidentified_name};
+use crate::types::{
+    CombinationNode, FamilyNode, GenusNode, IocEntry, NodeSummary, OrderNode, PhotoPage, PhotoSort,
+    ScanResponse, ScanStats, SearchHit, SpeciesNode, TaxonTree, VetoedMatch,
+};
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// The result of one scan, queried by the app, CLI and exports.
+pub struct ScanSession {
+    pub tree: TaxonTree,
+    pub stats: ScanStats,
+    pub total_species: usize,
+    pub roots: Vec<String>,
+    pub unmatched: Vec<String>,
+    pub entries: Vec<IocEntry>,
+    /// Lines of the alias file that were skipped when loading it.
+    pub alias_problems: Vec<String>,
+    /// Species overrides whose Latin name is not in the checklist; their
+    /// photos are matched by name instead.
+    pub override_problems: Vec<String>,
+    /// Unmatched files whose hit the exclusions rejected, sorted by path.
+    pub vetoed: Vec<VetoedMatch>,
+    entry_index: HashMap<String, usize>,
+    species_index: HashMap<String, (usize, usize, usize, usize)>,
+    genus_index: HashMap<String, (usize, usize, usize)>,
+    combination_index: HashMap<String, CombinationPos>,
+    photo_paths: HashSet<String>,
+    search_index: SearchIndex,
+}
+
+impl ScanSession {
+    pub fn new(
+        tree: TaxonTree,
+        stats: ScanStats,
+        roots: Vec<String>,
+        unmatched: Vec<String>,
+        entries: Vec<IocEntry>,
+    ) -> Self {
+        let entry_index = entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.latin.to_lowercase(), idx))
+            .collect();
+
+        let mut species_index = HashMap::new();
+        let mut genus_index = HashMap::new();
+        let mut combinations = vec![((None, None, None), &tree.combinations)];
+        let mut photo_paths: HashSet<String> = unmatched.iter().cloned().collect();
+        for (o, order) in tree.orders.iter().enumerate() {
+            combinations.push(((Some(o), None, None), &order.combinations));
+            for (f, family) in order.families.iter().enumerate() {
+                combinations.push(((Some(o), Some(f), None), &family.combinations));
+                for (g, genus) in family.genera.iter().enumerate() {
+                    genus_index.insert(genus.name.to_lowercase(), (o, f, g));
+                    combinations.push(((Some(o), Some(f), Some(g)), &genus.combinations));
+                    photo_paths.extend(
+                        genus
+                            .unidentified_photos
+                            .iter()
+                            .map(|photo| photo.path.clone()),
+                    );
+                    for (s, species) in genus.species.iter().enumerate() {
+                        species_index.insert(species.latin.to_lowercase(), (o, f, g, s));
+                        photo_paths.extend(species.photos.iter().map(|photo| photo.path.clone()));
+                    }
+                }
+            }
+        }
+        let mut combination_index = HashMap::new();
+        for ((o, f, g), nodes) in combinations {
+            for (c, combination) in nodes.iter().enumerate() {
+                combination_index.insert(combination.name.to_lowercase(), (o, f, g, c));
+                photo_paths.extend(combination.photos.iter().map(|photo| photo.path.clone()));
+            }
+        }
+
+        let search_index = SearchIndex::new(&entries, &tree, &unmatched);
+
+        Self {
+            tree,
+            stats,
+            total_species: entries.len(),
+            roots,
+            unmatched,
+            entries,
+            alias_problems: Vec::new(),
+            override_problems: Vec::new(),
+            vetoed: Vec::new(),
+            entry_index,
+            species_index,
+            genus_index,
+            combination_index,
+            photo_paths,
+            search_index,
+        }
+    }
+
+    pub fn response(&self) -> ScanResponse {
+        let tree = &self.tree;
+        ScanResponse {
+            tree: TaxonTree {
+                photographed_species: tree.photographed_species,
+                total_species: tree.total_species,
+                stats: tree.stats.clone(),
+                orders: tree.orders.iter().map(order_skeleton).collect(),
+                combinations: tree.combinations.iter().map(combination_skeleton).collect(),
+                low_confidence: tree.low_confidence,
+            },
+            stats: self.stats.clone(),
+            total_species: self.total_species,
+            alias_problems: self.alias_problems.clone(),
+            override_problems: self.override_problems.clone(),
+        }
+    }
+
+    /// Makes user aliases searchable.
+    pub fn index_aliases(&mut self, aliases: &AliasDictionary) {
+        self.search_index.add_aliases(&aliases.aliases);
+    }
+
+    pub fn entry(&self, latin: &str) -> Option<&IocEntry> {
+        let idx = *self.entry_index.get(&latin.to_lowercase())?;
+        self.entries.get(idx)
+    }
+
+    pub fn species(&self, latin: &str) -> Option<&SpeciesNode> {
+        let (o, f, g, s) = *self.species_index.get(&latin.to_lowercase())?;
+        Some(&self.tree.orders[o].families[f].genera[g].species[s])
+    }
+
+    pub fn genus(&self, name: &str) -> Option<&GenusNode> {
+        let (o, f, g) = *self.genus_index.get(&name.to_lowercase())?;
+        Some(&self.tree.orders[o].families[f].genera[g])
+    }
+
+    /// Looks up a hybrid or pair by its listed name.
+    pub fn combination(&self, name: &str) -> Option<&CombinationNode> {
+        let (o, f, g, c) = *self.combination_index.get(&name.to_lowercase())?;
+        let orders = &self.tree.orders;
+        let combinations = match (o, f, g) {
+            (Some(o), Some(f), Some(g)) => &orders[o].families[f].genera[g].combinations,
+            (Some(o), Some(f), None) => &orders[o].families[f].combinations,
+            (Some(o), _, _) => &orders[o].combinations,
+            _ => &self.tree.combinations,
+        };
+        combinations.get(c)
+    }
+
+    pub fn contains_photo(&self, path: &str) -> bool {
+        self.photo_paths.contains(path)
+    }
+
+    /// Ranks species names and photo paths: exact, prefix, substring, then fuzzy
+    /// matches, with species before photos at each level.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let species_count = |latin: &str| self.species(latin).map_or(0, |species| species.count);
+
+        let mut candidates = self.search_index.search(query, limit);
+        candidates.sort_by_cached_key(|candidate| match candidate.target {
+            Target::Species { entry, .. } => (
+                candidate.matched,
+                false,
+                candidate.distance,
+                Reverse(species_count(&self.entries[entry].latin) > 0),
+                self.entries[entry].seq,
+            ),
+            Target::Photo { index, .. } => (candidate.matched, true, 0, Reverse(false), index),
+        });
+        candidates.truncate(limit);
+
+        candidates
+            .into_iter()
+            .map(|candidate| {
+                let (entry, photo) = match candidate.target {
+                    Target::Species { entry, .. } => (Some(entry), None),
+                    Target::Photo { index, entry } => {
+                        (entry, Some(self.search_index.photo_path(index).to_string()))
+                    }
+                };
+                let text = match candidate.target {
+                    Target::Species { key, .. } => self.search_index.name_text(key).to_string(),
+                    Target::Photo { .. } => photo.clone().unwrap_or_default(),
+                };
+                let entry = entry.map(|idx| &self.entries[idx]);
+                SearchHit {
+                    matched: candidate.matched,
+                    field: candidate.field,
+                    text,
+                    latin: entry.map(|entry| entry.latin.clone()),
+                    chinese: entry.map(|entry| entry.chinese.clone()),
+                    photo,
+                    count: entry.map_or(0, |entry| species_count(&entry.latin)),
+                    tree_path: match candidate.target {
+                        Target::Species { .. } => entry
+                            .map(|entry| {
+                                vec![
+                                    entry.order.clone(),
+                                    entry.family.clone(),
+                                    genus_name(&entry.latin),
+                                    entry.latin.clone(),
+                                ]
+                            })
+                            .unwrap_or_default(),
+                        Target::Photo { index, .. } => {
+                            self.search_index.photo_location(index).to_vec()
+                        }
+                    },
+                }
+            })
+            .collect()
+    }
+
+    pub fn species_photos(
+        &self,
+        latin: &str,
+        offset: usize,
+        limit: usize,
+        sort: PhotoSort,
+    ) -> Result<PhotoPage> {
+        // `Genus sp.` selects the genus-only photos, a trinomial those of one
+        // subspecies, and a combination name the photos of a hybrid or pair.
+        let mut photos: Vec<_> = if let Some(combination) = self.combination(latin) {
+            combination.photos.iter().collect()
+        } else if let Some(genus) = unidentified_genus(latin) {
+            self.genus(genus)
+                .ok_or_else(|| anyhow!("Genus not found: {genus}"))?
+                .unidentified_photos
+                .iter()
+                .collect()
+        } else {
+            let mut words = latin.split_whitespace();
+            let binomial = words.by_ref().take(2).collect::<Vec<_>>().join(" ");
+            let subspecies = words.next().map(str::to_lowercase);
+            let species = self
+                .species(&binomial)
+                .ok_or_else(|| anyhow!("Species not found: {latin}"))?;
+            species
+                .photos
+                .iter()
+                .filter(|photo| subspecies.is_none() || photo.subspecies == subspecies)
+                .collect()
+        };
+        match sort {
+            PhotoSort::Name => {}
+            PhotoSort::Path => photos.sort_by(|a, b| a.path.cmp(&b.path)),
+            PhotoSort::Date => photos.sort_by(|a, b| a.taken_at.cmp(&b.taken_at)),
+        }
+
+        Ok(PhotoPage {
+            total: photos.len(),
+            offset,
+            photos: photos
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    pub fn species_under(
+        &self,
+        path: &[String],
+    ) -> Result<Vec<(&OrderNode, &FamilyNode, &GenusNode, &SpeciesNode)>> {
+        if path.len() > 4 {
+            return Err(anyhow!("Invalid tree path: {}", path.join(" > ")));
+        }
+        let selects = |depth: usize, name: &str| path.get(depth).is_none_or(|part| part == name);
+
+        let mut species_nodes = Vec::new();
+        for order in &self.tree.orders {
+            if !selects(0, &order.name) {
+                continue;
+            }
+            for family in &order.families {
+                if !selects(1, &family.name) {
+                    continue;
+                }
+                for genus in &family.genera {
+                    if !selects(2, &genus.name) {
+                        continue;
+                    }
+                    for species in &genus.species {
+                        if selects(3, &species.latin) {
+                            species_nodes.push((order, family, genus, species));
+                        }
+                    }
+                }
+            }
+        }
+
+        if species_nodes.is_empty() && !path.is_empty() {
+            return Err(anyhow!("Tree path not found: {}", path.join(" > ")));
+        }
+        Ok(species_nodes)
+    }
+
+    pub fn node_children(&self, path: &[String]) -> Result<Vec<NodeSummary>> {
+        let find = |kind: &str, name: &str| anyhow!("{kind} not found: {name}");
+        let find_order = |order: &str| {
+            self.tree
+                .orders
+                .iter()
+                .find(|node| node.name == order)
+                .ok_or_else(|| find("Order", order))
+        };
+        let find_family = |order: &str, family: &str| {
+            find_order(order)?
+                .families
+                .iter()
+                .find(|node| node.name == family)
+                .ok_or_else(|| find("Family", family))
+        };
+        let find_genus = |order: &str, family: &str, genus: &str| {
+            find_family(order, family)?
+                .genera
+                .iter()
+                .find(|node| node.name == genus)
+                .ok_or_else(|| find("Genus", genus))
+        };
+
+        let children = match path {
+            [] => self
+                .tree
+                .orders
+                .iter()
+                .map(|order| NodeSummary {
+                    name: order.name.clone(),
+                    chinese: None,
+                    count: order.count,
+                    photographed_species: order.photographed_species,
+                    total_species: order.total_species,
+                    children: order.families.len() + order.combinations.len(),
+                })
+                .chain(self.tree.combinations.iter().map(combination_summary))
+                .collect(),
+            [order] => {
+                let order = find_order(order)?;
+                order
+                    .families
+                    .iter()
+                    .map(|family| NodeSummary {
+                        name: family.name.clone(),
+                        chinese: None,
+                        count: family.count,
+                        photographed_species: family.photographed_species,
+                        total_species: family.total_species,
+                        children: family.genera.len() + family.combinations.len(),
+                    })
+                    .chain(order.combinations.iter().map(combination_summary))
+                    .collect()
+            }
+            [order, family] => {
+                let family = find_family(order, family)?;
+                family
+                    .genera
+                    .iter()
+                    .map(|genus| NodeSummary {
+                        name: genus.name.clone(),
+                        chinese: None,
+                        count: genus.count,
+                        photographed_species: genus.photographed_species,
+                        total_species: genus.total_species,
+                        children: genus.species.len()
+                            + usize::from(genus.unidentified > 0)
+                            + genus.combinations.len(),
+                    })
+                    .chain(family.combinations.iter().map(combination_summary))
+                    .collect()
+            }
+            [order, family, genus] => {
+                let genus = find_genus(order, family, genus)?;
+                let mut children: Vec<NodeSummary> = genus
+                    .species
+                    .iter()
+                    .map(|species| NodeSummary {
+                        name: species.latin.clone(),
+                        chinese: Some(species.chinese.clone()),
+                        count: species.count,
+                        photographed_species: usize::from(species.count > 0),
+                        total_species: 1,
+                        children: species.subspecies.len(),
+                    })
+                    .collect();
+                if genus.unidentified > 0 {
+                    children.push(NodeSummary {
+                        name: unidentified_name(&genus.name),
+                        chinese: None,
+                        count: genus.unidentified,
+                        photographed_species: 0,
+                        total_species: 0,
+                        children: 0,
+                    });
+                }
+                children.extend(genus.combinations.iter().map(combination_summary));
+                children
+            }
+            [order, family, genus, latin] => find_genus(order, family, genus)?
+                .species
+                .iter()
+                .find(|node| &node.latin == latin)
+                .ok_or_else(|| find("Species", latin))?
+                .subspecies
+                .iter()
+                .map(|subspecies| NodeSummary {
+                    name: subspecies.latin.clone(),
+                    chinese: None,
+                    count: subspecies.count,
+                    photographed_species: usize::from(subspecies.count > 0),
+                    total_species: 1,
+                    children: 0,
+                })
+                .collect(),
+            _ => return Err(anyhow!("Invalid tree path: {}", path.join(" > "))),
+        };
+
+        Ok(children)
+    }
+}
+
+/// Copies an order node and everything below it without the photo lists.
+fn order_skeleton(order: &OrderNode) -> OrderNode {
+    OrderNode {
+        name: order.name.clone(),
+        count: order.count,
+        photographed_species: order.photographed_species,
+        total_species: order.total_species,
+        stats: order.stats.clone(),
+        families: order.families.iter().map(family_skeleton).collect(),
+        unidentified: order.unidentified,
+        combined: order.combined,
+        combinations: order
+            .combinations
+            .iter()
+            .map(combination_skeleton)
+            .collect(),
+        low_confidence: order.low_confidence,
+    }
+}
+
+fn family_skeleton(family: &FamilyNode) -> FamilyNode {
+    FamilyNode {
+        name: family.name.clone(),
+        count: family.count,
+        photographed_species: family.photographed_species,
+        total_species: family.total_species,
+        stats: family.stats.clone(),
+        genera: family.genera.iter().map(genus_skeleton).collect(),
+        unidentified: family.unidentified,
+        combined: family.combined,
+        combinations: family
+            .combinations
+            .iter()
+            .map(combination_skeleton)
+            .collect(),
+        low_confidence: family.low_confidence,
+    }
+}
+
+fn genus_skeleton(genus: &GenusNode) -> GenusNode {
+    GenusNode {
+        name: genus.name.clone(),
+        count: genus.count,
+        photographed_species: genus.photographed_species,
+        total_species: genus.total_species,
+        stats: genus.stats.clone(),
+        species: genus.species.iter().map(species_skeleton).collect(),
+        unidentified: genus.unidentified,
+        unidentified_photos: Vec::new(),
+        combined: genus.combined,
+        combinations: genus
+            .combinations
+            .iter()
+            .map(combination_skeleton)
+            .collect(),
+        low_confidence: genus.low_confidence,
+    }
+}
+
+fn species_skeleton(species: &SpeciesNode) -> SpeciesNode {
+    SpeciesNode {
+        latin: species.latin.clone(),
+        chinese: species.chinese.clone(),
+        count: species.count,
+        stats: species.stats.clone(),
+        photos: Vec::new(),
+        subspecies: species.subspecies.clone(),
+        low_confidence: species.low_confidence,
+    }
+}
+
+fn combination_skeleton(combination: &CombinationNode) -> CombinationNode {
+    CombinationNode {
+        name: combination.name.clone(),
+        chinese: combination.chinese.clone(),
+        kind: combination.kind,
+        species: combination.species.clone(),
+        count: combination.count,
+        stats: combination.stats.clone(),
+        photos: Vec::new(),
+    }
+}
+
+fn combination_summary(combination: &CombinationNode) -> NodeSummary {
+    NodeSummary {
+        name: combination.name.clone(),
+        chinese: (!combination.chinese.is_empty()).then(|| combination.chinese.clone()),
+        count: combination.count,
+        photographed_species: 0,
+        total_species: 0,
+        children: 0,
+    }
+}
+
+/// Where a combination node sits: order, family and genus if below the tree
+/// root.
+type CombinationPos = (Option<usize>, Option<usize>, Option<usize>, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::ScanSession;
+    use crate::tree::build_tree;
+    use crate::types::{
+        CombinationKind, DateSource, Identification, IocEntry, MatchSource, MatchedPhoto,
+        PhotoSort, ScanStats, TreeOptions,
+    };
+
+    fn entry(
+        seq: usize,
+        order: &str,
+        family: &str,
+        latin: &str,
+        chinese: &str,
+        english: &str,
+    ) -> IocEntry {
+        IocEntry {
+            seq,
+            order: order.to_string(),
+            family: family.to_string(),
+            latin: latin.to_string(),
+            chinese: chinese.to_string(),
+            english: english.to_string(),
+            subspecies: Vec::new(),
+            other_names: Vec::new(),
+        }
+    }
+
+    fn photo(path: &str, species_idx: usize, taken_at: &str) -> MatchedPhoto {
+        MatchedPhoto {
+            path: path.to_string(),
+            file_name: path.rsplit('/').next().unwrap().to_string(),
+            species_idx,
+            identification: Identification::Species,
+            subspecies: None,
+            taken_at: Some(taken_at.to_string()),
+            date_source: DateSource::Exif,
+            latitude: None,
+            longitude: None,
+            location: None,
+            sequence: None,
+            source: MatchSource::Latin,
+            confidence: 90,
+        }
+    }
+
+    /// Three bulbul photos, a warbler, a genus-only warbler and a duck hybrid.
+    fn session() -> ScanSession {
+        let entries = vec![
+            entry(
+                0,
+                "Anseriformes",
+                "Anatidae",
+                "Anas platyrhynchos",
+                "绿头鸭",
+                "Mallard",
+            ),
+            entry(
+                1,
+                "Anseriformes",
+                "Anatidae",
+                "Anas zonorhyncha",
+                "斑嘴鸭",
+                "Eastern Spot-billed Duck",
+            ),
+            entry(
+                2,
+                "Passeriformes",
+                "Pycnonotidae",
+                "Pycnonotus sinensis",
+                "白头鹎",
+                "Light-vented Bulbul",
+            ),
+            entry(
+                3,
+                "Passeriformes",
+                "Pycnonotidae",
+                "Pycnonotus jocosus",
+                "红耳鹎",
+                "Red-whiskered Bulbul",
+            ),
+            entry(
+                4,
+                "Passeriformes",
+                "Phylloscopidae",
+                "Phylloscopus inornatus",
+                "黄眉柳莺",
+                "Yellow-browed Warbler",
+            ),
+        ];
+        let matches = [
+            photo("/photos/bulbul/c.jpg", 2, "2024-05-03 08:00:00"),
+            photo("/photos/bulbul/a.jpg", 2, "2023-01-02 09:00:00"),
+            photo("/photos/bulbul/b.jpg", 2, "2024-05-03 07:00:00"),
+            photo("/photos/warbler.jpg", 4, "2022-11-20 10:00:00"),
+            MatchedPhoto {
+                identification: Identification::Genus,
+                ..photo("/photos/leaf warbler sp.jpg", 4, "2024-12-01 10:00:00")
+            },
+            MatchedPhoto {
+                identification: Identification::Combination {
+                    kind: CombinationKind::Hybrid,
+                    other: 1,
+                },
+                ..photo("/photos/mallard hybrid.jpg", 0, "2021-03-04 10:00:00")
+            },
+        ];
+        let tree = build_tree(&entries, &matches, TreeOptions::default());
+        let stats = ScanStats {
+            total_files: matches.len(),
+            matched_files: matches.len(),
+            unmatched_files: 1,
+            ignored_files: 0,
+            vetoed_files: 0,
+        };
+        ScanSession::new(
+            tree,
+            stats,
+            vec!["/photos".to_string()],
+            vec!["/photos/DSC_0001.jpg".to_string()],
+            entries,
+        )
+    }
+
+    fn path(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|part| part.to_string()).collect()
+    }
+
+    #[test]
+    fn lists_node_children_level_by_level() {
+        let session = session();
+        let names = |parts: &[&str]| {
+            session
+                .node_children(&path(parts))
+                .unwrap()
+                .into_iter()
+                .map(|node| (node.name, node.count, node.children))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(&[]),
+            [
+                ("Anseriformes".to_string(), 0, 1),
+                ("Passeriformes".to_string(), 4, 2)
+            ]
+        );
+        assert_eq!(
+            names(&["Anseriformes", "Anatidae", "Anas"]),
+            [("Anas platyrhynchos × Anas zonorhyncha".to_string(), 1, 0)]
+        );
+        assert_eq!(
+            names(&["Passeriformes", "Phylloscopidae", "Phylloscopus"]),
+            [
+                ("Phylloscopus inornatus".to_string(), 1, 0),
+                ("Phylloscopus sp.".to_string(), 1, 0),
+            ]
+        );
+        assert!(session
+            .node_children(&path(&["Passeriformes", "Laridae"]))
+            .is_err());
+        assert!(session
+            .node_children(&path(&["a", "b", "c", "d", "e"]))
+            .is_err());
+    }
+
+    #[test]
+    fn pages_species_photos() {
+        let session = session();
+        let page = |offset: usize, limit: usize, sort: PhotoSort| {
+            let page = session
+                .species_photos("Pycnonotus sinensis", offset, limit, sort)
+                .unwrap();
+            let names: Vec<String> = page
+                .photos
+                .into_iter()
+                .map(|photo| photo.file_name)
+                .collect();
+            (page.total, names)
+        };
+
+        assert_eq!(
+            page(0, 2, PhotoSort::Path),
+            (3, vec!["a.jpg".into(), "b.jpg".into()])
+        );
+        assert_eq!(page(2, 2, PhotoSort::Path), (3, vec!["c.jpg".into()]));
+        assert_eq!(page(3, 2, PhotoSort::Path), (3, Vec::new()));
+        assert_eq!(page(1, 1, PhotoSort::Date), (3, vec!["b.jpg".into()]));
+
+        let genus_only = session
+            .species_photos("Phylloscopus sp.", 0, 10, PhotoSort::Name)
+            .unwrap();
+        assert_eq!(genus_only.total, 1);
+        let hybrid = session
+            .species_photos(
+                "anas platyrhynchos × anas zonorhyncha",
+                0,
+                10,
+                PhotoSort::Name,
+            )
+            .unwrap();
+        assert_eq!(hybrid.photos[0].file_name, "mallard hybrid.jpg");
+        assert!(session
+            .species_photos("Pycnonotus aurigaster", 0, 10, PhotoSort::Name)
+            .is_err());
+    }
+
+    #[test]
+    fn ranks_search_hits_with_tree_locations() {
+        use crate::types::SearchMatch::{Exact, Fuzzy, Prefix, Substring};
+
+        let session = session();
+        let hits = |query: &str| {
+            session
+                .search(query, 50)
+                .into_iter()
+                .map(|hit| (hit.matched, hit.text, hit.tree_path.last().cloned()))
+                .collect::<Vec<_>>()
+        };
+        let hit = |matched, text: &str, node: Option<&str>| {
+            (matched, text.to_string(), node.map(str::to_string))
+        };
+
+        assert_eq!(
+            hits("mallard"),
+            [
+                hit(Exact, "Mallard", Some("Anas platyrhynchos")),
+                hit(
+                    Prefix,
+                    "/photos/mallard hybrid.jpg",
+                    Some("Anas platyrhynchos × Anas zonorhyncha")
+                ),
+            ]
+        );
+        assert_eq!(
+            hits("warbler"),
+            [
+                hit(
+                    Prefix,
+                    "Yellow-browed Warbler",
+                    Some("Phylloscopus inornatus")
+                ),
+                hit(
+                    Prefix,
+                    "/photos/warbler.jpg",
+                    Some("Phylloscopus inornatus")
+                ),
+                hit(
+                    Substring,
+                    "/photos/leaf warbler sp.jpg",
+                    Some("Phylloscopus sp.")
+                ),
+            ]
+        );
+        // Photographed species first among equal hits.
+        assert_eq!(
+            hits("bulbul")[..3],
+            [
+                hit(Prefix, "Light-vented Bulbul", Some("Pycnonotus sinensis")),
+                hit(Prefix, "Red-whiskered Bulbul", Some("Pycnonotus jocosus")),
+                hit(
+                    Substring,
+                    "/photos/bulbul/a.jpg",
+                    Some("Pycnonotus sinensis")
+                ),
+            ]
+        );
+        assert_eq!(
+            hits("mallad"),
+            [hit(Fuzzy, "Mallard", Some("Anas platyrhynchos"))]
+        );
+        assert_eq!(hits("dsc"), [hit(Prefix, "/photos/DSC_0001.jpg", None)]);
+
+        let genus_only = &session.search("leaf warbler", 50)[0];
+        assert_eq!(
+            genus_only.tree_path,
+            [
+                "Passeriformes",
+                "Phylloscopidae",
+                "Phylloscopus",
+                "Phylloscopus sp."
+            ]
+        );
+        assert_eq!(
+            session.search("hybrid", 50)[0].tree_path[..3],
+            ["Anseriformes", "Anatidae", "Anas"]
+        );
+    }
+}
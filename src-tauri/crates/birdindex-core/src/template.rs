@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+
+/// Fields a template may capture.
+const FIELDS: [&str; 4] = ["species", "date", "location", "seq"];
+
+/// File naming conventions such as `{date}_{location}_{species}_{seq}`, or a
+/// regex with named groups like `^(?P<date>\d{8})_(?P<species>.+)$`. Only the
+/// `species` segment is given to the name matcher; the other segments become
+/// photo metadata. The first template matching a file stem wins.
+#[derive(Debug, Default)]
+pub struct FileTemplates {
+    templates: Vec<Regex>,
+}
+
+/// Segments captured from a file stem.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateFields {
+    pub species: Option<String>,
+    /// Capture date as `YYYY-MM-DD`, without a time of day.
+    pub date: Option<String>,
+    pub location: Option<String>,
+    pub sequence: Option<String>,
+}
+
+impl FileTemplates {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn compile(templates: &[String]) -> Result<Self> {
+        let templates = templates
+            .iter()
+            .map(|template| template.trim())
+            .filter(|template| !template.is_empty())
+            .map(compile_template)
+            .collect::<Result<_>>()?;
+        Ok(Self { templates })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    pub fn extract(&self, stem: &str) -> Option<TemplateFields> {
+        let captures = self
+            .templates
+            .iter()
+            .find_map(|template| template.captures(stem))?;
+        let field = |name: &str| {
+            captures
+                .name(name)
+                .map(|value| value.as_str().trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Some(TemplateFields {
+            species: field("species"),
+            date: field("date").and_then(|date| parse_date(&date)),
+            location: field("location"),
+            sequence: field("seq"),
+        })
+    }
+}
+
+fn compile_template(template: &str) -> Result<Regex> {
+    let pattern =
+        if template.starts_with('^') || template.contains("(?P<") || template.contains("(?<") {
+            template.to_string()
+        } else {
+            placeholder_pattern(template)?
+        };
+    let regex = Regex::new(&pattern)
+        .with_context(|| format!("Failed to compile file name template: {template}"))?;
+    if let Some(name) = regex
+        .capture_names()
+        .flatten()
+        .find(|name| !FIELDS.contains(name))
+    {
+        return Err(anyhow!(
+            "Unknown field `{name}` in file name template {template}; expected one of {}",
+            FIELDS.join(", ")
+        ));
+    }
+    Ok(regex)
+}
+
+/// Turns `{date}_{location}_{species}_{seq}` into an anchored regex. `{skip}`
+/// matches a segment that is not kept.
+fn placeholder_pattern(template: &str) -> Result<String> {
+    let mut pattern = String::from("^");
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        pattern.push_str(&regex::escape(&rest[..start]));
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("Unclosed placeholder in file name template: {template}"))?;
+        let name = &rest[start + 1..start + end];
+        pattern.push_str(match name {
+            "species" => "(?P<species>.+?)",
+            "location" => "(?P<location>.+?)",
+            "date" => r"(?P<date>\d{4}-?\d{2}-?\d{2})",
+            "seq" => r"(?P<seq>\d+)",
+            "skip" => ".+?",
+            _ => {
+                return Err(anyhow!(
+                    "Unknown placeholder {{{name}}} in file name template {template}; expected {{skip}} or one of {}",
+                    FIELDS.join(", ")
+                ))
+            }
+        });
+        rest = &rest[start + end + 1..];
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern.push('$');
+    Ok(pattern)
+}
+
+fn parse_date(date: &str) -> Option<String> {
+    let digits: String = date.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() != 8 {
+        return None;
+    }
+    let month: u32 = digits[4..6].parse().ok()?;
+    let day: u32 = digits[6..8].parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..8]
+    ))
+}
@@ -0,0 +1,866 @@
+use crate::metadata::{date_part, year_part};
+use crate::types::{
+    CombinationKind, CombinationNode, FamilyNode, GenusNode, Identification, IocEntry,
+    MatchedPhoto, OrderNode, PhotoItem, PhotoStats, SpeciesNode, SubspeciesNode, TaxonTree,
+    TreeOptions, TreeSort,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// The distinct dates behind `PhotoStats::shooting_days`, kept while building.
+type Days = BTreeSet<String>;
+
+/// Groups matches into order, family, genus and species nodes. Genus-only
+/// matches are kept on their genus, and hybrids and pairs on the lowest rank
+/// both species share (the tree root if none), outside the species counts.
+/// Photos scored below `options.min_confidence` are left out; their species,
+/// genus or shared rank counts them as `low_confidence`.
+pub fn build_tree(
+    entries: &[IocEntry],
+    matches: &[MatchedPhoto],
+    options: TreeOptions,
+) -> TaxonTree {
+    let mut orders: HashMap<String, OrderAgg> = HashMap::new();
+    let mut combinations: HashMap<String, CombinationAgg> = HashMap::new();
+    let mut low_confidence = 0;
+
+    for entry in entries {
+        genus_agg(&mut orders, entry).total_species += 1;
+        if options.full_tree {
+            species_agg(&mut orders, entry);
+        }
+    }
+
+    for matched in matches {
+        let entry = &entries[matched.species_idx];
+        if matched.confidence < options.min_confidence {
+            let count = match matched.identification {
+                Identification::Species => &mut species_agg(&mut orders, entry).low_confidence,
+                Identification::Genus => &mut genus_agg(&mut orders, entry).low_confidence,
+                Identification::Combination { other, .. } => {
+                    let (first, second) = in_ioc_order(entry, &entries[other]);
+                    match shared_rank(first, second) {
+                        Rank::Genus => &mut genus_agg(&mut orders, first).low_confidence,
+                        Rank::Family => &mut family_agg(&mut orders, first).low_confidence,
+                        Rank::Order => &mut order_agg(&mut orders, first).low_confidence,
+                        Rank::Root => &mut low_confidence,
+                    }
+                }
+            };
+            *count += 1;
+            continue;
+        }
+        let photo = PhotoItem {
+            path: matched.path.clone(),
+            file_name: matched.file_name.clone(),
+            taken_at: matched.taken_at.clone(),
+            date_source: matched.date_source,
+            latitude: matched.latitude,
+            longitude: matched.longitude,
+            subspecies: matched.subspecies.clone(),
+            location: matched.location.clone(),
+            sequence: matched.sequence.clone(),
+            source: matched.source,
+            confidence: matched.confidence,
+        };
+        match matched.identification {
+            Identification::Species => species_agg(&mut orders, entry).photos.push(photo),
+            Identification::Genus => genus_agg(&mut orders, entry).unidentified.push(photo),
+            Identification::Combination { kind, other } => {
+                let (first, second) = in_ioc_order(entry, &entries[other]);
+                let shared = match shared_rank(first, second) {
+                    Rank::Genus => &mut genus_agg(&mut orders, first).combinations,
+                    Rank::Family => &mut family_agg(&mut orders, first).combinations,
+                    Rank::Order => &mut order_agg(&mut orders, first).combinations,
+                    Rank::Root => &mut combinations,
+                };
+                shared
+                    .entry(combination_name(kind, &first.latin, &second.latin))
+                    .or_insert_with(|| CombinationAgg::new(kind, first, second))
+                    .photos
+                    .push(photo);
+            }
+        }
+    }
+
+    let order_nodes: Vec<(usize, (OrderNode, Days))> = orders
+        .into_iter()
+        .map(|(name, agg)| (agg.seq, agg.into_node(name, options)))
+        .collect();
+    let level = finish_level(order_nodes, options);
+
+    TaxonTree {
+        photographed_species: level.photographed_species,
+        total_species: level.total_species,
+        stats: level.stats,
+        orders: level.nodes,
+        combinations: finish_combinations(combinations, options),
+        low_confidence: level.low_confidence + low_confidence,
+    }
+}
+
+/// The lowest rank two species share.
+enum Rank {
+    Genus,
+    Family,
+    Order,
+    Root,
+}
+
+fn shared_rank(first: &IocEntry, second: &IocEntry) -> Rank {
+    if first.order != second.order {
+        Rank::Root
+    } else if first.family != second.family {
+        Rank::Order
+    } else if genus_name(&first.latin) != genus_name(&second.latin) {
+        Rank::Family
+    } else {
+        Rank::Genus
+    }
+}
+
+fn in_ioc_order<'a>(a: &'a IocEntry, b: &'a IocEntry) -> (&'a IocEntry, &'a IocEntry) {
+    if b.seq < a.seq {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+fn order_agg<'a>(orders: &'a mut HashMap<String, OrderAgg>, entry: &IocEntry) -> &'a mut OrderAgg {
+    let order = orders
+        .entry(entry.order.clone())
+        .or_insert_with(|| OrderAgg::new(entry.seq));
+    order.seq = order.seq.min(entry.seq);
+    order
+}
+
+fn family_agg<'a>(
+    orders: &'a mut HashMap<String, OrderAgg>,
+    entry: &IocEntry,
+) -> &'a mut FamilyAgg {
+    let family = order_agg(orders, entry)
+        .families
+        .entry(entry.family.clone())
+        .or_insert_with(|| FamilyAgg::new(entry.seq));
+    family.seq = family.seq.min(entry.seq);
+    family
+}
+
+fn genus_agg<'a>(orders: &'a mut HashMap<String, OrderAgg>, entry: &IocEntry) -> &'a mut GenusAgg {
+    let genus = family_agg(orders, entry)
+        .genera
+        .entry(genus_name(&entry.latin))
+        .or_insert_with(|| GenusAgg::new(entry.seq));
+    genus.seq = genus.seq.min(entry.seq);
+    genus
+}
+
+fn species_agg<'a>(
+    orders: &'a mut HashMap<String, OrderAgg>,
+    entry: &IocEntry,
+) -> &'a mut SpeciesAgg {
+    genus_agg(orders, entry)
+        .species
+        .entry(entry.latin.clone())
+        .or_insert_with(|| SpeciesAgg {
+            seq: entry.seq,
+            latin: entry.latin.clone(),
+            chinese: entry.chinese.clone(),
+            known_subspecies: entry.subspecies.clone(),
+            photos: Vec::new(),
+            low_confidence: 0,
+        })
+}
+
+/// Name under which genus-only photos are listed, e.g. `Phylloscopus sp.`.
+pub fn unidentified_name(genus: &str) -> String {
+    format!("{genus} sp.")
+}
+
+/// The genus of an `unidentified_name`, accepting `sp`, `sp.`, `spp` and `spp.`.
+pub fn unidentified_genus(name: &str) -> Option<&str> {
+    let (genus, marker) = name.trim().split_once(' ')?;
+    matches!(marker.trim(), "sp" | "sp." | "spp" | "spp.").then_some(genus)
+}
+
+/// Name under which a hybrid or pair is listed, e.g.
+/// `Anas platyrhynchos × Anas zonorhyncha`.
+pub fn combination_name(kind: CombinationKind, first: &str, second: &str) -> String {
+    match kind {
+        CombinationKind::Hybrid => format!("{first} × {second}"),
+        CombinationKind::Pair => format!("{first} / {second}"),
+    }
+}
+
+pub fn genus_name(latin: &str) -> String {
+    latin
+        .split_whitespace()
+        .next()
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+trait TreeNode {
+    fn sort_name(&self) -> &str;
+    fn count(&self) -> usize;
+    fn total_species(&self) -> usize;
+    fn photographed_species(&self) -> usize;
+    fn unidentified(&self) -> usize;
+    fn combined(&self) -> usize;
+    fn low_confidence(&self) -> usize;
+    fn stats(&self) -> &PhotoStats;
+}
+
+impl TreeNode for OrderNode {
+    fn sort_name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn total_species(&self) -> usize {
+        self.total_species
+    }
+
+    fn photographed_species(&self) -> usize {
+        self.photographed_species
+    }
+
+    fn unidentified(&self) -> usize {
+        self.unidentified
+    }
+
+    fn combined(&self) -> usize {
+        self.combined
+    }
+
+    fn low_confidence(&self) -> usize {
+        self.low_confidence
+    }
+
+    fn stats(&self) -> &PhotoStats {
+        &self.stats
+    }
+}
+
+impl TreeNode for FamilyNode {
+    fn sort_name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn total_species(&self) -> usize {
+        self.total_species
+    }
+
+    fn photographed_species(&self) -> usize {
+        self.photographed_species
+    }
+
+    fn unidentified(&self) -> usize {
+        self.unidentified
+    }
+
+    fn combined(&self) -> usize {
+        self.combined
+    }
+
+    fn low_confidence(&self) -> usize {
+        self.low_confidence
+    }
+
+    fn stats(&self) -> &PhotoStats {
+        &self.stats
+    }
+}
+
+impl TreeNode for GenusNode {
+    fn sort_name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn total_species(&self) -> usize {
+        self.total_species
+    }
+
+    fn photographed_species(&self) -> usize {
+        self.photographed_species
+    }
+
+    fn unidentified(&self) -> usize {
+        self.unidentified
+    }
+
+    fn combined(&self) -> usize {
+        self.combined
+    }
+
+    fn low_confidence(&self) -> usize {
+        self.low_confidence
+    }
+
+    fn stats(&self) -> &PhotoStats {
+        &self.stats
+    }
+}
+
+impl TreeNode for SpeciesNode {
+    fn sort_name(&self) -> &str {
+        &self.latin
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn total_species(&self) -> usize {
+        1
+    }
+
+    fn photographed_species(&self) -> usize {
+        usize::from(self.count > 0)
+    }
+
+    fn unidentified(&self) -> usize {
+        0
+    }
+
+    fn combined(&self) -> usize {
+        0
+    }
+
+    fn low_confidence(&self) -> usize {
+        self.low_confidence
+    }
+
+    fn stats(&self) -> &PhotoStats {
+        &self.stats
+    }
+}
+
+struct Level<T> {
+    nodes: Vec<T>,
+    total_species: usize,
+    photographed_species: usize,
+    unidentified: usize,
+    combined: usize,
+    low_confidence: usize,
+    stats: PhotoStats,
+    days: Days,
+}
+
+fn finish_level<T: TreeNode>(mut nodes: Vec<(usize, (T, Days))>, options: TreeOptions) -> Level<T> {
+    let total_species = nodes
+        .iter()
+        .map(|(_, (node, _))| node.total_species())
+        .sum();
+    if !options.full_tree {
+        nodes.retain(|(_, (node, _))| {
+            node.count() > 0
+                || node.unidentified() > 0
+                || node.combined() > 0
+                || node.low_confidence() > 0
+        });
+    }
+    let photographed_species = nodes
+        .iter()
+        .map(|(_, (node, _))| node.photographed_species())
+        .sum();
+    let unidentified = nodes.iter().map(|(_, (node, _))| node.unidentified()).sum();
+    let combined = nodes.iter().map(|(_, (node, _))| node.combined()).sum();
+    let low_confidence = nodes
+        .iter()
+        .map(|(_, (node, _))| node.low_confidence())
+        .sum();
+    let mut stats = PhotoStats::default();
+    let mut days = Days::new();
+    for (_, (node, node_days)) in &nodes {
+        merge_stats(&mut stats, &mut days, node.stats(), node_days);
+    }
+
+    match options.sort {
+        TreeSort::Ioc => nodes.sort_by_key(|(seq, _)| *seq),
+        TreeSort::Alphabetical => {
+            nodes.sort_by(|(_, (a, _)), (_, (b, _))| a.sort_name().cmp(b.sort_name()))
+        }
+        TreeSort::Count => nodes.sort_by(|(seq_a, (a, _)), (seq_b, (b, _))| {
+            b.count().cmp(&a.count()).then_with(|| seq_a.cmp(seq_b))
+        }),
+    }
+
+    Level {
+        nodes: nodes.into_iter().map(|(_, (node, _))| node).collect(),
+        total_species,
+        photographed_species,
+        unidentified,
+        combined,
+        low_confidence,
+        stats,
+        days,
+    }
+}
+
+fn add_photo_stats(stats: &mut PhotoStats, days: &mut Days, taken_at: &str) {
+    widen_range(stats, taken_at, taken_at);
+    days.insert(date_part(taken_at).to_string());
+    stats.shooting_days = days.len();
+    if let Some(year) = year_part(taken_at) {
+        *stats.by_year.entry(year).or_insert(0) += 1;
+    }
+}
+
+fn merge_stats(stats: &mut PhotoStats, days: &mut Days, other: &PhotoStats, other_days: &Days) {
+    if let (Some(first), Some(last)) = (&other.first_taken, &other.last_taken) {
+        widen_range(stats, first, last);
+    }
+    days.extend(other_days.iter().cloned());
+    stats.shooting_days = days.len();
+    for (year, count) in &other.by_year {
+        *stats.by_year.entry(*year).or_insert(0) += count;
+    }
+}
+
+fn widen_range(stats: &mut PhotoStats, first: &str, last: &str) {
+    if stats
+        .first_taken
+        .as_deref()
+        .is_none_or(|current| first < current)
+    {
+        stats.first_taken = Some(first.to_string());
+    }
+    if stats
+        .last_taken
+        .as_deref()
+        .is_none_or(|current| last > current)
+    {
+        stats.last_taken = Some(last.to_string());
+    }
+}
+
+struct OrderAgg {
+    seq: usize,
+    families: HashMap<String, FamilyAgg>,
+    combinations: HashMap<String, CombinationAgg>,
+    /// Combination photos below the minimum confidence.
+    low_confidence: usize,
+}
+
+impl OrderAgg {
+    fn new(seq: usize) -> Self {
+        Self {
+            seq,
+            families: HashMap::new(),
+            combinations: HashMap::new(),
+            low_confidence: 0,
+        }
+    }
+
+    fn into_node(self, name: String, options: TreeOptions) -> (OrderNode, Days) {
+        let families: Vec<(usize, (FamilyNode, Days))> = self
+            .families
+            .into_iter()
+            .map(|(name, agg)| (agg.seq, agg.into_node(name, options)))
+            .collect();
+        let level = finish_level(families, options);
+        let combinations = finish_combinations(self.combinations, options);
+        let count = level.nodes.iter().map(|f| f.count).sum();
+        let node = OrderNode {
+            name,
+            count,
+            photographed_species: level.photographed_species,
+            total_species: level.total_species,
+            stats: level.stats,
+            families: level.nodes,
+            unidentified: level.unidentified,
+            combined: level.combined + combination_count(&combinations),
+            combinations,
+            low_confidence: level.low_confidence + self.low_confidence,
+        };
+        (node, level.days)
+    }
+}
+
+struct FamilyAgg {
+    seq: usize,
+    genera: HashMap<String, GenusAgg>,
+    combinations: HashMap<String, CombinationAgg>,
+    /// Combination photos below the minimum confidence.
+    low_confidence: usize,
+}
+
+impl FamilyAgg {
+    fn new(seq: usize) -> Self {
+        Self {
+            seq,
+            genera: HashMap::new(),
+            combinations: HashMap::new(),
+            low_confidence: 0,
+        }
+    }
+
+    fn into_node(self, name: String, options: TreeOptions) -> (FamilyNode, Days) {
+        let genera: Vec<(usize, (GenusNode, Days))> = self
+            .genera
+            .into_iter()
+            .map(|(name, agg)| (agg.seq, agg.into_node(name, options)))
+            .collect();
+        let level = finish_level(genera, options);
+        let combinations = finish_combinations(self.combinations, options);
+        let count = level.nodes.iter().map(|g| g.count).sum();
+        let node = FamilyNode {
+            name,
+            count,
+            photographed_species: level.photographed_species,
+            total_species: level.total_species,
+            stats: level.stats,
+            genera: level.nodes,
+            unidentified: level.unidentified,
+            combined: level.combined + combination_count(&combinations),
+            combinations,
+            low_confidence: level.low_confidence + self.low_confidence,
+        };
+        (node, level.days)
+    }
+}
+
+struct GenusAgg {
+    seq: usize,
+    /// IOC species in the genus, photographed or not.
+    total_species: usize,
+    species: HashMap<String, SpeciesAgg>,
+    unidentified: Vec<PhotoItem>,
+    combinations: HashMap<String, CombinationAgg>,
+    /// Genus-only and combination photos below the minimum confidence.
+    low_confidence: usize,
+}
+
+impl GenusAgg {
+    fn new(seq: usize) -> Self {
+        Self {
+            seq,
+            total_species: 0,
+            species: HashMap::new(),
+            unidentified: Vec::new(),
+            combinations: HashMap::new(),
+            low_confidence: 0,
+        }
+    }
+
+    fn into_node(mut self, name: String, options: TreeOptions) -> (GenusNode, Days) {
+        self.unidentified
+            .sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        let species: Vec<(usize, (SpeciesNode, Days))> = self
+            .species
+            .into_values()
+            .map(|agg| (agg.seq, agg.into_node(options)))
+            .collect();
+        let level = finish_level(species, options);
+        let combinations = finish_combinations(self.combinations, options);
+        let count = level.nodes.iter().map(|s| s.count).sum();
+        let node = GenusNode {
+            name,
+            count,
+            photographed_species: level.photographed_species,
+            total_species: self.total_species,
+            stats: level.stats,
+            species: level.nodes,
+            unidentified: self.unidentified.len(),
+            unidentified_photos: self.unidentified,
+            combined: combination_count(&combinations),
+            combinations,
+            low_confidence: level.low_confidence + self.low_confidence,
+        };
+        (node, level.days)
+    }
+}
+
+struct SpeciesAgg {
+    seq: usize,
+    latin: String,
+    chinese: String,
+    known_subspecies: Vec<String>,
+    photos: Vec<PhotoItem>,
+    low_confidence: usize,
+}
+
+impl SpeciesAgg {
+    fn into_node(mut self, options: TreeOptions) -> (SpeciesNode, Days) {
+        self.photos.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        let count = self.photos.len();
+        let mut stats = PhotoStats::default();
+        let mut days = Days::new();
+        for photo in &self.photos {
+            if let Some(taken_at) = &photo.taken_at {
+                add_photo_stats(&mut stats, &mut days, taken_at);
+            }
+        }
+        let subspecies = if options.subspecies {
+            self.subspecies_nodes(options)
+        } else {
+            Vec::new()
+        };
+        let node = SpeciesNode {
+            latin: self.latin,
+            chinese: self.chinese,
+            count,
+            stats,
+            photos: self.photos,
+            subspecies,
+            low_confidence: self.low_confidence,
+        };
+        (node, days)
+    }
+
+    fn subspecies_nodes(&self, options: TreeOptions) -> Vec<SubspeciesNode> {
+        let mut nodes: HashMap<&str, (SubspeciesNode, Days)> = HashMap::new();
+        if options.full_tree {
+            for epithet in &self.known_subspecies {
+                nodes.insert(epithet, (self.subspecies_node(epithet), Days::new()));
+            }
+        }
+        for photo in &self.photos {
+            let Some(epithet) = &photo.subspecies else {
+                continue;
+            };
+            let (node, days) = nodes
+                .entry(epithet)
+                .or_insert_with(|| (self.subspecies_node(epithet), Days::new()));
+            node.count += 1;
+            if let Some(taken_at) = &photo.taken_at {
+                add_photo_stats(&mut node.stats, days, taken_at);
+            }
+        }
+
+        let seq = |name: &str| {
+            self.known_subspecies
+                .iter()
+                .position(|epithet| epithet == name)
+                .unwrap_or(usize::MAX)
+        };
+        let mut nodes: Vec<SubspeciesNode> = nodes.into_values().map(|(node, _)| node).collect();
+        match options.sort {
+            TreeSort::Ioc => nodes.sort_by(|a, b| {
+                seq(&a.name)
+                    .cmp(&seq(&b.name))
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            TreeSort::Alphabetical => nodes.sort_by(|a, b| a.name.cmp(&b.name)),
+            TreeSort::Count => {
+                nodes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)))
+            }
+        }
+        nodes
+    }
+
+    fn subspecies_node(&self, epithet: &str) -> SubspeciesNode {
+        SubspeciesNode {
+            name: epithet.to_string(),
+            latin: format!("{} {epithet}", self.latin),
+            count: 0,
+            stats: PhotoStats::default(),
+        }
+    }
+}
+
+struct CombinationAgg {
+    seq: (usize, usize),
+    kind: CombinationKind,
+    species: Vec<String>,
+    chinese: String,
+    photos: Vec<PhotoItem>,
+}
+
+impl CombinationAgg {
+    fn new(kind: CombinationKind, first: &IocEntry, second: &IocEntry) -> Self {
+        let chinese = if first.chinese.is_empty() || second.chinese.is_empty() {
+            String::new()
+        } else {
+            combination_name(kind, &first.chinese, &second.chinese)
+        };
+        Self {
+            seq: (first.seq, second.seq),
+            kind,
+            species: vec![first.latin.clone(), second.latin.clone()],
+            chinese,
+            photos: Vec::new(),
+        }
+    }
+
+    fn into_node(mut self, name: String) -> CombinationNode {
+        self.photos.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        let mut stats = PhotoStats::default();
+        let mut days = Days::new();
+        for photo in &self.photos {
+            if let Some(taken_at) = &photo.taken_at {
+                add_photo_stats(&mut stats, &mut days, taken_at);
+            }
+        }
+        CombinationNode {
+            name,
+            chinese: self.chinese,
+            kind: self.kind,
+            species: self.species,
+            count: self.photos.len(),
+            stats,
+            photos: self.photos,
+        }
+    }
+}
+
+fn finish_combinations(
+    combinations: HashMap<String, CombinationAgg>,
+    options: TreeOptions,
+) -> Vec<CombinationNode> {
+    let mut nodes: Vec<((usize, usize), CombinationNode)> = combinations
+        .into_iter()
+        .map(|(name, agg)| (agg.seq, agg.into_node(name)))
+        .collect();
+    match options.sort {
+        TreeSort::Ioc => nodes.sort_by_key(|(seq, _)| *seq),
+        TreeSort::Alphabetical => nodes.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name)),
+        TreeSort::Count => nodes
+            .sort_by(|(seq_a, a), (seq_b, b)| b.count.cmp(&a.count).then_with(|| seq_a.cmp(seq_b))),
+    }
+    nodes.into_iter().map(|(_, node)| node).collect()
+}
+
+fn combination_count(combinations: &[CombinationNode]) -> usize {
+    combinations.iter().map(|node| node.count).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_tree;
+    use crate::types::{
+        DateSource, Identification, IocEntry, MatchSource, MatchedPhoto, PhotoStats, TreeOptions,
+    };
+
+    fn entry(seq: usize, order: &str, family: &str, latin: &str) -> IocEntry {
+        IocEntry {
+            seq,
+            order: order.to_string(),
+            family: family.to_string(),
+            latin: latin.to_string(),
+            chinese: String::new(),
+            english: String::new(),
+            subspecies: Vec::new(),
+            other_names: Vec::new(),
+        }
+    }
+
+    fn photo(species_idx: usize, taken_at: Option<&str>) -> MatchedPhoto {
+        MatchedPhoto {
+            path: format!("/photos/{species_idx}-{taken_at:?}.jpg"),
+            file_name: format!("{species_idx}-{taken_at:?}.jpg"),
+            species_idx,
+            identification: Identification::Species,
+            subspecies: None,
+            taken_at: taken_at.map(str::to_string),
+            date_source: DateSource::Exif,
+            latitude: None,
+            longitude: None,
+            location: None,
+            sequence: None,
+            source: MatchSource::Latin,
+            confidence: 90,
+        }
+    }
+
+    fn summary(stats: &PhotoStats) -> (&str, &str, usize, Vec<(i32, usize)>) {
+        (
+            stats.first_taken.as_deref().unwrap_or_default(),
+            stats.last_taken.as_deref().unwrap_or_default(),
+            stats.shooting_days,
+            stats
+                .by_year
+                .iter()
+                .map(|(year, count)| (*year, *count))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn rolls_photo_stats_up_the_tree() {
+        let entries = [
+            entry(0, "Anseriformes", "Anatidae", "Anas platyrhynchos"),
+            entry(
+                1,
+                "Passeriformes",
+                "Phylloscopidae",
+                "Phylloscopus inornatus",
+            ),
+            entry(2, "Passeriformes", "Pycnonotidae", "Pycnonotus jocosus"),
+            entry(3, "Passeriformes", "Pycnonotidae", "Pycnonotus sinensis"),
+        ];
+        let matches = [
+            photo(0, Some("2021-03-04 10:00:00")),
+            photo(1, Some("2022-11-20 10:00:00")),
+            photo(2, Some("2024-05-03 09:00:00")),
+            photo(2, Some("2025-02-01 09:00:00")),
+            photo(3, Some("2024-05-03 17:00:00")),
+            photo(3, Some("2023-01-02 08:00:00")),
+            photo(3, Some("2024-05-03 08:00:00")),
+            photo(3, None),
+            // Genus-only photos stay out of the aggregates, like the counts.
+            MatchedPhoto {
+                identification: Identification::Genus,
+                ..photo(3, Some("2020-01-01 08:00:00"))
+            },
+        ];
+        let tree = build_tree(&entries, &matches, TreeOptions::default());
+        let passeriformes = &tree.orders[1];
+        let pycnonotus = &passeriformes.families[1].genera[0];
+        assert_eq!(pycnonotus.name, "Pycnonotus");
+
+        assert_eq!(
+            summary(&pycnonotus.species[1].stats),
+            (
+                "2023-01-02 08:00:00",
+                "2024-05-03 17:00:00",
+                2,
+                vec![(2023, 1), (2024, 2)]
+            )
+        );
+        // Days shared by two species count once.
+        assert_eq!(
+            summary(&pycnonotus.stats),
+            (
+                "2023-01-02 08:00:00",
+                "2025-02-01 09:00:00",
+                3,
+                vec![(2023, 1), (2024, 3), (2025, 1)]
+            )
+        );
+        assert_eq!(
+            summary(&passeriformes.families[1].stats),
+            summary(&pycnonotus.stats)
+        );
+        assert_eq!(
+            summary(&passeriformes.stats),
+            (
+                "2022-11-20 10:00:00",
+                "2025-02-01 09:00:00",
+                4,
+                vec![(2022, 1), (2023, 1), (2024, 3), (2025, 1)]
+            )
+        );
+        assert_eq!(
+            summary(&tree.stats),
+            (
+                "2021-03-04 10:00:00",
+                "2025-02-01 09:00:00",
+                5,
+                vec![(2021, 1), (2022, 1), (2023, 1), (2024, 3), (2025, 1)]
+            )
+        );
+    }
+}
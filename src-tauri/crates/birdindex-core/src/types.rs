@@ -0,0 +1,560 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IocEntry {
+    pub seq: usize,
+    pub order: String,
+    pub family: String,
+    pub latin: String,
+    pub chinese: String,
+    pub english: String,
+    #[serde(default)]
+    pub subspecies: Vec<String>,
+    /// Names from the workbook's other language columns, e.g. French or Japanese.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_names: Vec<String>,
+}
+
+/// A banding code with the species it stands for; several when ambiguous.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlphaCode {
+    pub code: String,
+    pub species: Vec<IocEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IocChange {
+    pub before: IocEntry,
+    pub after: IocEntry,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IocDiff {
+    pub added: Vec<IocEntry>,
+    pub removed: Vec<IocEntry>,
+    pub changed: Vec<IocChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoItem {
+    pub path: String,
+    pub file_name: String,
+    pub taken_at: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub subspecies: Option<String>,
+    /// Location segment of the file name, from a file name template.
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Sequence segment of the file name, from a file name template.
+    #[serde(default)]
+    pub sequence: Option<String>,
+    #[serde(default)]
+    pub source: MatchSource,
+    #[serde(default)]
+    pub confidence: u8,
+    #[serde(default)]
+    pub date_source: DateSource,
+}
+
+/// Where a photo's `taken_at` came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateSource {
+    /// The EXIF capture time.
+    Exif,
+    /// The date segment of a file name template.
+    FileName,
+    #[default]
+    Mtime,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhotoStats {
+    pub first_taken: Option<String>,
+    pub last_taken: Option<String>,
+    pub shooting_days: usize,
+    pub by_year: BTreeMap<i32, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeciesNode {
+    pub latin: String,
+    pub chinese: String,
+    pub count: usize,
+    pub stats: PhotoStats,
+    pub photos: Vec<PhotoItem>,
+    #[serde(default)]
+    pub subspecies: Vec<SubspeciesNode>,
+    /// Photos below `TreeOptions::min_confidence`, left out of `count`.
+    #[serde(default)]
+    pub low_confidence: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubspeciesNode {
+    pub name: String,
+    pub latin: String,
+    pub count: usize,
+    pub stats: PhotoStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenusNode {
+    pub name: String,
+    pub count: usize,
+    pub photographed_species: usize,
+    pub total_species: usize,
+    pub stats: PhotoStats,
+    pub species: Vec<SpeciesNode>,
+    /// Photos identified only to this genus ("Phylloscopus sp."), not in `count`.
+    #[serde(default)]
+    pub unidentified: usize,
+    #[serde(default)]
+    pub unidentified_photos: Vec<PhotoItem>,
+    /// Photos in hybrids and pairs at or below this node, not in `count`.
+    #[serde(default)]
+    pub combined: usize,
+    #[serde(default)]
+    pub combinations: Vec<CombinationNode>,
+    /// Photos of species below this node left out by `min_confidence`.
+    #[serde(default)]
+    pub low_confidence: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CombinationKind {
+    /// `Anas platyrhynchos x Anas zonorhyncha`
+    Hybrid,
+    /// `Phylloscopus borealis/examinandus`, one of two species
+    Pair,
+}
+
+/// Photos of one hybrid or species pair, kept on the lowest rank the two
+/// species share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinationNode {
+    pub name: String,
+    pub chinese: String,
+    pub kind: CombinationKind,
+    pub species: Vec<String>,
+    pub count: usize,
+    pub stats: PhotoStats,
+    pub photos: Vec<PhotoItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FamilyNode {
+    pub name: String,
+    pub count: usize,
+    pub photographed_species: usize,
+    pub total_species: usize,
+    pub stats: PhotoStats,
+    pub genera: Vec<GenusNode>,
+    #[serde(default)]
+    pub unidentified: usize,
+    #[serde(default)]
+    pub combined: usize,
+    #[serde(default)]
+    pub combinations: Vec<CombinationNode>,
+    #[serde(default)]
+    pub low_confidence: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderNode {
+    pub name: String,
+    pub count: usize,
+    pub photographed_species: usize,
+    pub total_species: usize,
+    pub stats: PhotoStats,
+    pub families: Vec<FamilyNode>,
+    #[serde(default)]
+    pub unidentified: usize,
+    #[serde(default)]
+    pub combined: usize,
+    #[serde(default)]
+    pub combinations: Vec<CombinationNode>,
+    #[serde(default)]
+    pub low_confidence: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxonTree {
+    pub photographed_species: usize,
+    pub total_species: usize,
+    pub stats: PhotoStats,
+    pub orders: Vec<OrderNode>,
+    /// Hybrids and pairs of species from different orders.
+    #[serde(default)]
+    pub combinations: Vec<CombinationNode>,
+    #[serde(default)]
+    pub low_confidence: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanStats {
+    pub total_files: usize,
+    pub matched_files: usize,
+    pub unmatched_files: usize,
+    /// Files left out by an `ignore` override.
+    #[serde(default)]
+    pub ignored_files: usize,
+    /// Unmatched files whose hit the exclusions rejected.
+    #[serde(default)]
+    pub vetoed_files: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeSort {
+    #[default]
+    Ioc,
+    Alphabetical,
+    Count,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TreeOptions {
+    #[serde(default)]
+    pub sort: TreeSort,
+    #[serde(default)]
+    pub full_tree: bool,
+    #[serde(default)]
+    pub subspecies: bool,
+    /// Leave out photos matched with a lower confidence (0–100).
+    #[serde(default)]
+    pub min_confidence: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchOptions {
+    #[serde(default)]
+    pub traditional: bool,
+    #[serde(default)]
+    pub pinyin: bool,
+    #[serde(default)]
+    pub pinyin_initials: bool,
+    #[serde(default)]
+    pub strip_accents: bool,
+    /// Four-letter English and six-letter Latin banding codes (`LVBU`, `PYCSIN`).
+    #[serde(default)]
+    pub alpha_codes: bool,
+    /// English names as whole words (`Light-vented Bulbul`).
+    #[serde(default)]
+    pub english_names: bool,
+    /// Latin binomials with one typo in the genus or the epithet.
+    #[serde(default)]
+    pub fuzzy_latin: bool,
+}
+
+/// Stop-list consulted by the name matcher.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExclusionOptions {
+    /// Text that never counts toward a hit, e.g. a place name containing a
+    /// Chinese bird name.
+    #[serde(default)]
+    pub never_match: Vec<String>,
+    /// Words that reject any hit in the same file name, e.g. `not`, `vs`, `cf.`.
+    #[serde(default)]
+    pub veto_words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanRequest {
+    pub roots: Vec<String>,
+    #[serde(flatten)]
+    pub tree: TreeOptions,
+    #[serde(flatten)]
+    pub matching: MatchOptions,
+    #[serde(flatten)]
+    pub exclusions: ExclusionOptions,
+    /// File name templates, see [`crate::template::FileTemplates`].
+    #[serde(default)]
+    pub templates: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResponse {
+    pub tree: TaxonTree,
+    pub stats: ScanStats,
+    pub total_species: usize,
+    #[serde(default)]
+    pub alias_problems: Vec<String>,
+    #[serde(default)]
+    pub override_problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhotoSort {
+    #[default]
+    Name,
+    Path,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoPage {
+    pub total: usize,
+    pub offset: usize,
+    pub photos: Vec<PhotoItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSummary {
+    pub name: String,
+    pub chinese: Option<String>,
+    pub count: usize,
+    pub photographed_species: usize,
+    pub total_species: usize,
+    pub children: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMatch {
+    Exact,
+    Prefix,
+    Substring,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Latin,
+    Chinese,
+    Traditional,
+    Pinyin,
+    English,
+    /// A name from another language column of the workbook.
+    OtherLanguage,
+    /// A user alias.
+    Alias,
+    Path,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub matched: SearchMatch,
+    pub field: SearchField,
+    pub text: String,
+    pub latin: Option<String>,
+    pub chinese: Option<String>,
+    pub photo: Option<String>,
+    pub count: usize,
+    pub tree_path: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    #[serde(default)]
+    pub year: Option<i32>,
+    #[serde(default)]
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifeListRequest {
+    pub output: String,
+    pub format: ExportFormat,
+    #[serde(flatten)]
+    pub filter: ExportFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DarwinCoreRequest {
+    pub output: String,
+    #[serde(default)]
+    pub path: Vec<String>,
+    #[serde(flatten)]
+    pub filter: ExportFilter,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GalleryLinks {
+    #[default]
+    Relative,
+    Absolute,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GalleryRequest {
+    pub output: String,
+    #[serde(default)]
+    pub links: GalleryLinks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifeListRow {
+    pub seq: usize,
+    pub latin: String,
+    pub chinese: String,
+    pub english: String,
+    pub order: String,
+    pub family: String,
+    pub count: usize,
+    pub first_taken: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewResponse {
+    pub path: String,
+    pub placeholder: bool,
+    pub error: Option<String>,
+}
+
+/// How far a file name identified its bird.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Identification {
+    #[default]
+    Species,
+    /// Genus only ("Phylloscopus sp."); `species_idx` is the first IOC entry
+    /// of the genus.
+    Genus,
+    /// A hybrid or pair of `species_idx` and `other`.
+    Combination { kind: CombinationKind, other: usize },
+}
+
+/// What a match was read from: the first matcher tier that hit, or an
+/// override.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    /// A manual override.
+    Override,
+    #[default]
+    Latin,
+    Chinese,
+    Alias,
+    /// A banding code.
+    Code,
+    /// An English name, as a keyword of whole words.
+    Keyword,
+    Traditional,
+    Pinyin,
+    PinyinInitials,
+    /// A genus followed by `sp.`.
+    Genus,
+    /// A Latin binomial with one typo.
+    Fuzzy,
+    /// The name of a folder above a file whose own name has no hit.
+    Folder,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchedPhoto {
+    pub path: String,
+    pub file_name: String,
+    pub species_idx: usize,
+    pub identification: Identification,
+    pub subspecies: Option<String>,
+    pub taken_at: Option<String>,
+    pub date_source: DateSource,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub location: Option<String>,
+    pub sequence: Option<String>,
+    pub source: MatchSource,
+    /// 0–100, see `matcher::confidence`.
+    pub confidence: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub path: String,
+    pub mtime: i64,
+    pub species_latin: Option<String>,
+    #[serde(default)]
+    pub subspecies: Option<String>,
+    /// Set instead of `species_latin` for genus-only identifications.
+    #[serde(default)]
+    pub genus: Option<String>,
+    /// The second species of a hybrid or pair.
+    #[serde(default)]
+    pub combined_with: Option<(CombinationKind, String)>,
+    /// Species and reason of a hit the exclusions rejected.
+    #[serde(default)]
+    pub vetoed: Option<(String, String)>,
+    /// `Override` on entries that only keep the metadata of an overridden
+    /// file.
+    #[serde(default)]
+    pub source: MatchSource,
+    #[serde(default)]
+    pub confidence: u8,
+    #[serde(default)]
+    pub taken_at: Option<String>,
+    #[serde(default)]
+    pub date_source: DateSource,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+}
+
+/// A file left unmatched because the exclusions rejected its hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VetoedMatch {
+    pub path: String,
+    pub latin: String,
+    pub reason: String,
+}
+
+/// What a manual override does with a photo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+pub enum OverrideAction {
+    /// Classify as this IOC species; a trinomial also sets the subspecies.
+    Species { latin: String },
+    /// Leave the photo out of the index.
+    Ignore,
+}
+
+/// A manual classification, keyed by path or by file content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoOverride {
+    pub path: String,
+    /// `<size>:<sha256>` of the file, so the override follows it when moved.
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(flatten)]
+    pub action: OverrideAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideRequest {
+    pub path: String,
+    #[serde(flatten)]
+    pub action: OverrideAction,
+    #[serde(default)]
+    pub by_content: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideFile {
+    pub version: u32,
+    pub overrides: Vec<PhotoOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheFile {
+    pub version: u32,
+    pub ioc_fingerprint: String,
+    pub entries: Vec<CacheEntry>,
+}
+
+impl CacheFile {}
@@ -0,0 +1,7 @@
+/// 64-bit FNV-1a, stable across Rust releases, for names of files kept
+/// between runs.
+pub fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
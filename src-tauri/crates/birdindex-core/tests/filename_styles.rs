@@ -0,0 +1,500 @@
+use birdindex_core::aliases::AliasDictionary;
+use birdindex_core::codes::AlphaCodes;
+#[cfg(feature = "xlsx")]
+use birdindex_core::ioc::IocDatabase;
+use birdindex_core::matcher::{NameMatcher, Veto};
+use birdindex_core::template::FileTemplates;
+use birdindex_core::tree::{build_tree, combination_name, genus_name};
+use birdindex_core::types::{
+    CombinationKind, CombinationNode, DateSource, ExclusionOptions, Identification, IocEntry,
+    MatchOptions, MatchSource, MatchedPhoto, TreeOptions,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+const CORPUS: &str = include_str!("data/filename_styles.tsv");
+
+const ALIASES: &str = "alias,latin
+LVB,Pycnonotus sinensis
+白头,Pycnonotus sinensis
+\"WHWA\", Motacilla  alba
+LVB,Passer montanus
+JUNK,Nonexistus avis
+柳莺,Phylloscopus
+鹟莺,Seicercus
+";
+
+/// The `List` sheet of a small IOC workbook, tab-separated.
+const IOC: &str = include_str!("data/ioc_synthetic.tsv");
+
+/// The `Master` sheet of the same workbook: genus and species on their own
+/// row, each subspecies on a row below.
+const SUBSPECIES: &str = include_str!("data/ioc_subspecies.tsv");
+
+fn entries() -> Vec<IocEntry> {
+    let mut subspecies: HashMap<String, Vec<String>> = HashMap::new();
+    let mut species = String::new();
+    for line in SUBSPECIES.lines().skip(1) {
+        match line.split('\t').collect::<Vec<_>>()[..] {
+            [genus, epithet, ""] => species = format!("{genus} {epithet}"),
+            [_, _, epithet] => subspecies
+                .entry(species.clone())
+                .or_default()
+                .push(epithet.to_string()),
+            _ => panic!("bad subspecies line: {line}"),
+        }
+    }
+
+    IOC.lines()
+        .skip(1)
+        .enumerate()
+        .map(|(seq, line)| {
+            let cells: Vec<&str> = line.split('\t').collect();
+            IocEntry {
+                seq,
+                order: cells[0].to_string(),
+                family: cells[1].to_string(),
+                latin: cells[2].to_string(),
+                chinese: cells[4].to_string(),
+                english: cells[3].to_string(),
+                subspecies: subspecies.remove(cells[2]).unwrap_or_default(),
+                other_names: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn matches_filename_style_corpus() {
+    assert_corpus(&entries());
+}
+
+/// Writes the synthetic checklist to a real workbook, so the corpus also runs
+/// through the IOC loader.
+#[cfg(feature = "xlsx")]
+#[test]
+fn matches_corpus_with_synthetic_workbook() {
+    let path = std::env::temp_dir().join(format!("birdindex-ioc-{}.xlsx", std::process::id()));
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    for (name, data) in [("List", IOC), ("Master", SUBSPECIES)] {
+        let sheet = workbook.add_worksheet().set_name(name).unwrap();
+        for (row, line) in data.lines().enumerate() {
+            for (col, cell) in line.split('\t').enumerate() {
+                sheet.write_string(row as u32, col as u16, cell).unwrap();
+            }
+        }
+    }
+    workbook.save(&path).unwrap();
+    let ioc = IocDatabase::load(&path);
+    std::fs::remove_file(&path).ok();
+    let ioc = ioc.unwrap();
+
+    let fields = |entry: &IocEntry| {
+        (
+            entry.seq,
+            entry.order.clone(),
+            entry.family.clone(),
+            entry.latin.clone(),
+            entry.chinese.clone(),
+            entry.english.clone(),
+            entry.subspecies.clone(),
+        )
+    };
+    let expected: Vec<_> = entries().iter().map(fields).collect();
+    let loaded: Vec<_> = ioc.entries.iter().map(fields).collect();
+    assert_eq!(loaded, expected);
+    assert_corpus(&ioc.entries);
+}
+
+fn assert_corpus(entries: &[IocEntry]) {
+    let aliases = AliasDictionary::parse(ALIASES, &latin_index(entries));
+    let options = MatchOptions {
+        alpha_codes: true,
+        traditional: true,
+        pinyin: true,
+        pinyin_initials: true,
+        ..MatchOptions::default()
+    };
+    let matcher = NameMatcher::with_aliases(entries, options, &aliases);
+
+    let mut failures = Vec::new();
+    for line in CORPUS.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (file_name, expected) = line.split_once('\t').expect("tab-separated line");
+        let stem = Path::new(file_name).file_stem().unwrap().to_string_lossy();
+        let actual = match matcher.match_file(&stem) {
+            Some(hit) => {
+                let latin = &entries[hit.species_idx].latin;
+                match (hit.identification, hit.subspecies) {
+                    (Identification::Genus, _) => format!("{} sp.", genus_name(latin)),
+                    (Identification::Combination { kind, other }, _) => {
+                        combination_name(kind, latin, &entries[other].latin)
+                    }
+                    (_, Some(epithet)) => format!("{latin} {epithet}"),
+                    (_, None) => latin.clone(),
+                }
+            }
+            None => "-".to_string(),
+        };
+        if actual != expected {
+            failures.push(format!("{file_name}: expected {expected}, got {actual}"));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn reports_bad_alias_lines() {
+    let aliases = AliasDictionary::parse(ALIASES, &latin_index(&entries()));
+    assert_eq!(aliases.aliases.len(), 3);
+    assert_eq!(aliases.genera.len(), 1);
+    assert_eq!(aliases.problems.len(), 3, "{:?}", aliases.problems);
+    assert!(aliases.problems[1].contains("Nonexistus avis"));
+    assert!(aliases.problems[2].contains("unknown genus `Seicercus`"));
+}
+
+#[test]
+fn resolves_alpha_code_collisions() {
+    let entries: Vec<IocEntry> = [
+        ("Ardea cinerea", "Grey Heron"),
+        ("Ardea cinereocapilla", "Great Heron"),
+        ("Pandion haliaetus", "Osprey"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(seq, (latin, english))| IocEntry {
+        seq,
+        order: "Pelecaniformes".to_string(),
+        family: "Ardeidae".to_string(),
+        latin: latin.to_string(),
+        chinese: String::new(),
+        english: english.to_string(),
+        subspecies: Vec::new(),
+        other_names: Vec::new(),
+    })
+    .collect();
+    let codes = AlphaCodes::generate(&entries);
+
+    assert_eq!(codes.lookup("GRHE"), [0, 1]);
+    assert_eq!(codes.lookup("gher"), [0]);
+    assert_eq!(codes.lookup("GREH"), [1]);
+    assert_eq!(codes.lookup("ARDCIN"), [0, 1]);
+    assert_eq!(codes.lookup("ARCINE"), [0]);
+    assert_eq!(codes.lookup("ARDECI"), [1]);
+    assert_eq!(codes.lookup("OSPR"), [2]);
+    assert_eq!(codes.lookup("PANHAL"), [2]);
+    let ambiguous: Vec<&str> = codes.ambiguous().iter().map(|(code, _)| *code).collect();
+    assert_eq!(ambiguous, ["ARDCIN", "GRHE"]);
+}
+
+#[test]
+fn extracts_template_fields() {
+    let entries = entries();
+    let matcher = NameMatcher::new(&entries, MatchOptions::default());
+    let templates = FileTemplates::compile(&[
+        "{date}_{location}_{species}_{seq}".to_string(),
+        r"^(?P<species>[^ ]+) \((?P<location>.+)\)$".to_string(),
+    ])
+    .unwrap();
+
+    let fields = templates.extract("20240512_Nanhaizi_白头鹎_003").unwrap();
+    assert_eq!(fields.date.as_deref(), Some("2024-05-12"));
+    assert_eq!(fields.location.as_deref(), Some("Nanhaizi"));
+    assert_eq!(fields.sequence.as_deref(), Some("003"));
+    let species = fields.species.unwrap();
+    assert_eq!(matcher.match_name(&species), Some(0));
+
+    let fields = templates.extract("麻雀 (Beijing)").unwrap();
+    assert_eq!(fields.species.as_deref(), Some("麻雀"));
+    assert_eq!(fields.location.as_deref(), Some("Beijing"));
+    assert_eq!(fields.date, None);
+
+    assert_eq!(templates.extract("DSC_0001"), None);
+    assert!(FileTemplates::compile(&["{species}_{camera}".to_string()]).is_err());
+    assert!(FileTemplates::compile(&["^(?P<camera>.+)$".to_string()]).is_err());
+}
+
+#[test]
+fn applies_exclusions() {
+    let entries = entries();
+    let exclusions = ExclusionOptions {
+        never_match: vec!["麻雀山".to_string()],
+        veto_words: vec!["cf.".to_string(), "vs".to_string(), "不是".to_string()],
+    };
+    let matcher = NameMatcher::new(&entries, MatchOptions::default()).with_exclusions(&exclusions);
+    let species = |file_name: &str| {
+        matcher
+            .classify(file_name)
+            .map(|hit| hit.map(|hit| entries[hit.species_idx].latin.as_str()))
+    };
+
+    assert_eq!(species("麻雀山_白头鹎_01"), Ok(Some("Pycnonotus sinensis")));
+    assert_eq!(species("Passer_montanus_vsco"), Ok(Some("Passer montanus")));
+    assert_eq!(species("DSC_0001"), Ok(None));
+    assert_eq!(
+        species("麻雀山_001"),
+        Err(Veto {
+            species_idx: 2,
+            reason: "never match: 麻雀山".to_string(),
+        })
+    );
+    assert_eq!(
+        species("cf. Pycnonotus sinensis").unwrap_err().reason,
+        "veto word: cf."
+    );
+    let veto = species("Anas_platyrhynchos_vs_zonorhyncha").unwrap_err();
+    assert_eq!(entries[veto.species_idx].latin, "Anas platyrhynchos");
+    assert!(species("不是白头鹎").is_err());
+    assert_eq!(matcher.match_file("cf. Pycnonotus sinensis"), None);
+}
+
+#[test]
+fn scores_match_confidence() {
+    let entries = entries();
+    let matcher = NameMatcher::new(&entries, MatchOptions::default());
+    let score = |file_name: &str| {
+        let hit = matcher.match_file(file_name).unwrap();
+        (hit.source, hit.confidence)
+    };
+
+    assert_eq!(score("Pycnonotus_sinensis_001"), (MatchSource::Latin, 90));
+    assert_eq!(
+        score("白头鹎_Pycnonotus_sinensis"),
+        (MatchSource::Latin, 100)
+    );
+    assert_eq!(score("白头鹎_Passer_montanus"), (MatchSource::Latin, 70));
+    assert_eq!(score("2024白头鹎_001"), (MatchSource::Chinese, 85));
+    assert_eq!(score("白头鹎幼鸟"), (MatchSource::Chinese, 70));
+    assert_eq!(score("Phylloscopus sp"), (MatchSource::Genus, 60));
+    assert_eq!(
+        score("Anas platyrhynchos x domestic"),
+        (MatchSource::Latin, 70)
+    );
+    assert_eq!(
+        score("pycnonotus sinensis or not"),
+        (MatchSource::Latin, 90)
+    );
+
+    let folder = matcher.match_file("白头鹎");
+    let in_folder = |file_name: &str| {
+        let hit = matcher
+            .classify_in_folder(file_name, folder.as_ref())
+            .unwrap()
+            .unwrap();
+        (
+            entries[hit.species_idx].latin.as_str(),
+            hit.source,
+            hit.confidence,
+        )
+    };
+    assert_eq!(
+        in_folder("Pycnonotus_sinensis_001"),
+        ("Pycnonotus sinensis", MatchSource::Latin, 100)
+    );
+    assert_eq!(
+        in_folder("Passer_montanus_001"),
+        ("Passer montanus", MatchSource::Latin, 70)
+    );
+    assert_eq!(
+        in_folder("DSC_0001"),
+        ("Pycnonotus sinensis", MatchSource::Folder, 50)
+    );
+
+    let photo = |species_idx: usize, confidence: u8| MatchedPhoto {
+        path: format!("/photos/{species_idx}_{confidence}.jpg"),
+        file_name: format!("{species_idx}_{confidence}.jpg"),
+        species_idx,
+        identification: Identification::Species,
+        subspecies: None,
+        taken_at: None,
+        date_source: DateSource::Mtime,
+        latitude: None,
+        longitude: None,
+        location: None,
+        sequence: None,
+        source: MatchSource::Latin,
+        confidence,
+    };
+    let genus_only = MatchedPhoto {
+        identification: Identification::Genus,
+        ..photo(5, 60)
+    };
+    let hybrid = |other: usize| MatchedPhoto {
+        identification: Identification::Combination {
+            kind: CombinationKind::Hybrid,
+            other,
+        },
+        ..photo(10, 60)
+    };
+    let matches = [
+        photo(0, 90),
+        photo(0, 70),
+        photo(2, 40),
+        genus_only,
+        hybrid(11),
+        hybrid(0),
+    ];
+    let options = TreeOptions {
+        min_confidence: 80,
+        ..TreeOptions::default()
+    };
+    let tree = build_tree(&entries, &matches, options);
+    let species: Vec<(&str, usize, usize)> = tree
+        .orders
+        .iter()
+        .flat_map(|order| &order.families)
+        .flat_map(|family| &family.genera)
+        .flat_map(|genus| &genus.species)
+        .map(|species| {
+            (
+                species.latin.as_str(),
+                species.count,
+                species.low_confidence,
+            )
+        })
+        .collect();
+    assert_eq!(
+        species,
+        [("Pycnonotus sinensis", 1, 1), ("Passer montanus", 0, 1)]
+    );
+    let phylloscopus = tree
+        .orders
+        .iter()
+        .flat_map(|order| &order.families)
+        .flat_map(|family| &family.genera)
+        .find(|genus| genus.name == "Phylloscopus")
+        .unwrap();
+    assert_eq!(
+        (phylloscopus.unidentified, phylloscopus.low_confidence),
+        (0, 1)
+    );
+    // Mallard × Eastern Spot-billed Duck on Anas, Mallard × bulbul on the root.
+    let anseriformes = tree
+        .orders
+        .iter()
+        .find(|order| order.name == "Anseriformes")
+        .unwrap();
+    assert_eq!(anseriformes.families[0].genera[0].low_confidence, 1);
+    assert_eq!(anseriformes.low_confidence, 1);
+    assert_eq!(tree.photographed_species, 1);
+    assert_eq!(tree.total_species, entries.len());
+    assert_eq!(tree.low_confidence, 5);
+}
+
+#[test]
+fn matches_english_names_and_typos() {
+    let entries = entries();
+    let options = MatchOptions {
+        english_names: true,
+        fuzzy_latin: true,
+        ..MatchOptions::default()
+    };
+    let matcher = NameMatcher::new(&entries, options);
+    let hit = |file_name: &str| {
+        matcher.match_file(file_name).map(|hit| {
+            (
+                entries[hit.species_idx].latin.as_str(),
+                hit.source,
+                hit.confidence,
+            )
+        })
+    };
+
+    assert_eq!(
+        hit("Light-vented Bulbul 001"),
+        Some(("Pycnonotus sinensis", MatchSource::Keyword, 75))
+    );
+    assert_eq!(
+        hit("light_vented_bulbul_白头鹎"),
+        Some(("Pycnonotus sinensis", MatchSource::Chinese, 95))
+    );
+    assert_eq!(
+        hit("2024 mallard"),
+        Some(("Anas platyrhynchos", MatchSource::Keyword, 75))
+    );
+    assert_eq!(hit("mallards"), None);
+    assert_eq!(
+        hit("Pycnonotus sinnensis 001"),
+        Some(("Pycnonotus sinensis", MatchSource::Fuzzy, 55))
+    );
+    assert_eq!(
+        hit("Pycnonotis_sinensis"),
+        Some(("Pycnonotus sinensis", MatchSource::Fuzzy, 55))
+    );
+    assert_eq!(
+        hit("Phylloscopus borealus"),
+        Some(("Phylloscopus borealis", MatchSource::Fuzzy, 55))
+    );
+    // Two typos, and a typo in a word shorter than five letters.
+    assert_eq!(hit("Pycnonotsu sinensis"), None);
+    assert_eq!(hit("Ardea albo"), None);
+
+    let default = NameMatcher::new(&entries, MatchOptions::default());
+    assert_eq!(default.match_file("Light-vented Bulbul"), None);
+    assert_eq!(default.match_file("Pycnonotus sinnensis"), None);
+}
+
+#[test]
+fn files_combinations_on_shared_rank() {
+    let entries = entries();
+    let matcher = NameMatcher::new(&entries, MatchOptions::default());
+    let matches: Vec<MatchedPhoto> = [
+        "Anas platyrhynchos x Anas zonorhyncha",
+        "Passer montanus x Pycnonotus sinensis",
+        "Anas platyrhynchos x Pycnonotus sinensis",
+    ]
+    .into_iter()
+    .map(|file_name| {
+        let hit = matcher.match_file(file_name).unwrap();
+        MatchedPhoto {
+            path: format!("/photos/{file_name}.jpg"),
+            file_name: format!("{file_name}.jpg"),
+            species_idx: hit.species_idx,
+            identification: hit.identification,
+            subspecies: None,
+            taken_at: None,
+            date_source: DateSource::Mtime,
+            latitude: None,
+            longitude: None,
+            location: None,
+            sequence: None,
+            source: hit.source,
+            confidence: hit.confidence,
+        }
+    })
+    .collect();
+    let tree = build_tree(&entries, &matches, TreeOptions::default());
+
+    let names = |combinations: &[CombinationNode]| {
+        combinations
+            .iter()
+            .map(|node| node.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let order = |name: &str| tree.orders.iter().find(|order| order.name == name).unwrap();
+    let anas = &order("Anseriformes").families[0].genera[0];
+    assert_eq!(
+        names(&anas.combinations),
+        ["Anas platyrhynchos × Anas zonorhyncha"]
+    );
+    assert_eq!(
+        names(&order("Passeriformes").combinations),
+        ["Pycnonotus sinensis × Passer montanus"]
+    );
+    assert_eq!(
+        names(&tree.combinations),
+        ["Pycnonotus sinensis × Anas platyrhynchos"]
+    );
+    assert_eq!(tree.photographed_species, 0);
+}
+
+fn latin_index(entries: &[IocEntry]) -> HashMap<String, usize> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| (entry.latin.to_lowercase(), idx))
+        .collect()
+}
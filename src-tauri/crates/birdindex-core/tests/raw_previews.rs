@@ -0,0 +1,71 @@
+#![cfg(feature = "thumbnails")]
+
+use birdindex_core::preview::extract_embedded_jpeg;
+use std::fs;
+use std::path::PathBuf;
+
+const JPEG: [u8; 8] = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x00, 0xFF, 0xD9];
+
+fn write_raw(name: &str, data: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("birdindex-{}-{name}", std::process::id()));
+    fs::write(&path, data).unwrap();
+    path
+}
+
+fn ifd(entries: &[(u16, u32)], next: u32) -> Vec<u8> {
+    let mut data = (entries.len() as u16).to_le_bytes().to_vec();
+    for (tag, value) in entries {
+        data.extend(tag.to_le_bytes());
+        data.extend(4u16.to_le_bytes());
+        data.extend(1u32.to_le_bytes());
+        data.extend(value.to_le_bytes());
+    }
+    data.extend(next.to_le_bytes());
+    data
+}
+
+#[test]
+fn rejects_truncated_raf_headers() {
+    let short = write_raw("short.raf", b"FUJIFILMCCD-RAW 0201");
+    assert!(extract_embedded_jpeg(&short).is_err());
+
+    // Preview offset and length point far past the end of the file.
+    let mut header = b"FUJIFILMCCD-RAW 0201".to_vec();
+    header.resize(84, 0);
+    header.extend(100u32.to_be_bytes());
+    header.extend(u32::MAX.to_be_bytes());
+    let oversized = write_raw("oversized.raf", &header);
+    assert!(extract_embedded_jpeg(&oversized).is_err());
+
+    fs::remove_file(short).ok();
+    fs::remove_file(oversized).ok();
+}
+
+#[test]
+fn skips_bad_tiff_candidates() {
+    // IFD0 at 8 (42 bytes) names a preview running past the end of the file
+    // and a sub-IFD at 50 (30 bytes) with the real preview at 80.
+    let mut data = b"II*\0".to_vec();
+    data.extend(8u32.to_le_bytes());
+    data.extend(ifd(&[(0x014A, 50), (0x0201, 80), (0x0202, 1_000_000)], 0));
+    data.extend(ifd(&[(0x0201, 80), (0x0202, JPEG.len() as u32)], 0));
+    data.extend(JPEG);
+    let path = write_raw("preview.dng", &data);
+
+    assert_eq!(extract_embedded_jpeg(&path).unwrap(), JPEG);
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn stops_at_overflowing_cr3_box() {
+    // An ftyp box, then a box whose 64-bit size runs past u64::MAX.
+    let mut data = 16u32.to_be_bytes().to_vec();
+    data.extend(b"ftypcrx \0\0\0\0");
+    data.extend(1u32.to_be_bytes());
+    data.extend(b"moov");
+    data.extend((u64::MAX - 8).to_be_bytes());
+    let path = write_raw("overflow.cr3", &data);
+
+    assert!(extract_embedded_jpeg(&path).is_err());
+    fs::remove_file(path).ok();
+}
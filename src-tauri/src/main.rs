@@ -1,12 +1,25 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod core;
+mod locator;
 
-use core::{scan_and_build, types::ScanRequest};
-use std::path::PathBuf;
+use birdindex_core::ioc::IocDatabase;
+use birdindex_core::overrides::{self, OverrideStore};
+use birdindex_core::session::ScanSession;
+use birdindex_core::types::{
+    DarwinCoreRequest, GalleryRequest, IocDiff, LifeListRequest, OverrideRequest, PhotoOverride,
+    PhotoPage, PhotoSort, ScanRequest, ScanResponse, SearchHit, VetoedMatch,
+};
+use birdindex_core::{scan_and_build, search};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tauri::path::BaseDirectory;
 use tauri::Manager;
 
+#[derive(Default)]
+struct AppState {
+    session: Mutex<Option<ScanSession>>,
+}
+
 fn resolve_ioc_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let mut checked = Vec::new();
     let resource_candidates = [
@@ -53,6 +66,22 @@ fn resolve_cache_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(cache_dir.join("scan-cache.json"))
 }
 
+fn resolve_overrides_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    Ok(data_dir.join("overrides.json"))
+}
+
+fn load_overrides(app: &tauri::AppHandle) -> Result<OverrideStore, String> {
+    OverrideStore::load(resolve_overrides_path(app)?).map_err(|err| err.to_string())
+}
+
+fn resolve_preview_dir(app: &tauri::AppHandle) -> PathBuf {
+    app.path()
+        .app_cache_dir()
+        .unwrap_or_else(|_| std::env::temp_dir())
+        .join("previews")
+}
+
 fn clear_cache_file(app: &tauri::AppHandle) {
     if let Ok(path) = resolve_cache_path(app) {
         let _ = std::fs::remove_file(path);
@@ -60,30 +89,217 @@ fn clear_cache_file(app: &tauri::AppHandle) {
 }
 
 #[tauri::command]
-fn scan(app: tauri::AppHandle, request: ScanRequest) -> Result<core::types::ScanResponse, String> {
+fn scan(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    request: ScanRequest,
+) -> Result<ScanResponse, String> {
     let ioc_path = resolve_ioc_path(&app)?;
     let cache_path = resolve_cache_path(&app)?;
-    scan_and_build(request, &ioc_path, &cache_path).map_err(|err| err.to_string())
+    let overrides = load_overrides(&app)?;
+    let session = scan_and_build(request, &ioc_path, &cache_path, &overrides)
+        .map_err(|err| err.to_string())?;
+    let response = session.response();
+    *state.session.lock().map_err(|err| err.to_string())? = Some(session);
+    Ok(response)
+}
+
+fn with_session<T>(
+    state: &AppState,
+    f: impl FnOnce(&ScanSession) -> anyhow::Result<T>,
+) -> Result<T, String> {
+    let session = state.session.lock().map_err(|err| err.to_string())?;
+    let session = session.as_ref().ok_or("No scan result available")?;
+    f(session).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_species_photos(
+    state: tauri::State<'_, AppState>,
+    latin: String,
+    offset: usize,
+    limit: usize,
+    sort: Option<PhotoSort>,
+) -> Result<PhotoPage, String> {
+    with_session(&state, |session| {
+        session.species_photos(&latin, offset, limit, sort.unwrap_or_default())
+    })
+}
+
+#[tauri::command]
+fn search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    with_session(&state, |session| {
+        Ok(session.search(&query, limit.unwrap_or(search::DEFAULT_LIMIT)))
+    })
+}
+
+#[tauri::command]
+fn get_unmatched(
+    state: tauri::State<'_, AppState>,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<String>, String> {
+    with_session(&state, |session| {
+        Ok(session
+            .unmatched
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    })
+}
+
+#[tauri::command]
+fn get_vetoed(
+    state: tauri::State<'_, AppState>,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<VetoedMatch>, String> {
+    with_session(&state, |session| {
+        Ok(session
+            .vetoed
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    })
+}
+
+#[tauri::command]
+fn set_override(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    request: OverrideRequest,
+) -> Result<(), String> {
+    let session = state.session.lock().map_err(|err| err.to_string())?;
+    match session.as_ref() {
+        Some(session) => overrides::validate(&request.action, &session.entries),
+        None => IocDatabase::load(resolve_ioc_path(&app)?)
+            .and_then(|db| overrides::validate(&request.action, &db.entries)),
+    }
+    .map_err(|err| err.to_string())?;
+    drop(session);
+
+    let path = resolve_overrides_path(&app)?;
+    let mut store = load_overrides(&app)?;
+    store
+        .set(Path::new(&request.path), request.action, request.by_content)
+        .and_then(|_| store.save(path))
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn clear_override(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    let mut store = load_overrides(&app)?;
+    if !store.clear(Path::new(&path)) {
+        return Ok(false);
+    }
+    store
+        .save(resolve_overrides_path(&app)?)
+        .map_err(|err| err.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+fn list_overrides(app: tauri::AppHandle) -> Result<Vec<PhotoOverride>, String> {
+    Ok(load_overrides(&app)?.list().to_vec())
+}
+
+#[tauri::command]
+fn diff_ioc(app: tauri::AppHandle, path: String) -> Result<IocDiff, String> {
+    let current = IocDatabase::load(resolve_ioc_path(&app)?).map_err(|err| err.to_string())?;
+    let candidate = IocDatabase::load(path).map_err(|err| err.to_string())?;
+    Ok(birdindex_core::ioc::diff(&current, &candidate))
+}
+
+#[tauri::command]
+fn export_life_list(
+    state: tauri::State<'_, AppState>,
+    request: LifeListRequest,
+) -> Result<usize, String> {
+    with_session(&state, |session| {
+        let rows = birdindex_core::export::life_list(session, &request.filter);
+        birdindex_core::export::write_life_list(&rows, request.format, &request.output)?;
+        Ok(rows.len())
+    })
+}
+
+#[tauri::command]
+fn export_darwin_core(
+    state: tauri::State<'_, AppState>,
+    request: DarwinCoreRequest,
+) -> Result<usize, String> {
+    with_session(&state, |session| {
+        birdindex_core::export::write_darwin_core(
+            session,
+            &request.path,
+            &request.filter,
+            &request.output,
+        )
+    })
+}
+
+#[tauri::command]
+fn export_gallery(
+    state: tauri::State<'_, AppState>,
+    request: GalleryRequest,
+) -> Result<usize, String> {
+    with_session(&state, |session| {
+        birdindex_core::gallery::write_gallery(
+            &session.tree,
+            &session.roots,
+            std::path::Path::new(&request.output),
+            request.links,
+        )
+    })
+}
+
+#[tauri::command]
+fn preview(app: tauri::AppHandle, path: String) -> birdindex_core::types::PreviewResponse {
+    birdindex_core::preview::resolve_preview(path, &resolve_preview_dir(&app))
 }
 
 #[tauri::command]
 fn reveal(path: String) -> Result<(), String> {
-    core::locator::reveal_in_file_manager(path).map_err(|err| err.to_string())
+    locator::reveal_in_file_manager(path).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
 fn open_file(path: String) -> Result<(), String> {
-    core::locator::open_file(path).map_err(|err| err.to_string())
+    locator::open_file(path).map_err(|err| err.to_string())
 }
 
 fn main() {
     let app = tauri::Builder::default()
+        .manage(AppState::default())
         .setup(|app| {
             clear_cache_file(app.handle());
             Ok(())
         })
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![scan, reveal, open_file])
+        .invoke_handler(tauri::generate_handler![
+            scan,
+            get_species_photos,
+            search,
+            get_unmatched,
+            get_vetoed,
+            diff_ioc,
+            set_override,
+            clear_override,
+            list_overrides,
+            export_life_list,
+            export_darwin_core,
+            export_gallery,
+            preview,
+            reveal,
+            open_file
+        ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
@@ -1,52 +1,245 @@
 import { useEffect, useMemo, useRef, useState, type ChangeEvent } from "react";
 import { invoke, convertFileSrc, isTauri } from "@tauri-apps/api/core";
 import { appDataDir, join } from "@tauri-apps/api/path";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save } from "@tauri-apps/plugin-dialog";
 
 interface PhotoItem {
   path: string;
   file_name: string;
+  taken_at: string | null;
+  date_source: "exif" | "file_name" | "mtime";
+  subspecies: string | null;
+  location: string | null;
+  sequence: string | null;
+  source: MatchSource;
+  confidence: number;
+}
+
+type MatchSource =
+  | "override"
+  | "latin"
+  | "chinese"
+  | "alias"
+  | "code"
+  | "keyword"
+  | "traditional"
+  | "pinyin"
+  | "pinyin_initials"
+  | "genus"
+  | "fuzzy"
+  | "folder";
+
+interface PhotoStats {
+  first_taken: string | null;
+  last_taken: string | null;
+  shooting_days: number;
+  by_year: Record<string, number>;
 }
 
 interface SpeciesNode {
   latin: string;
   chinese: string;
   count: number;
+  stats: PhotoStats;
   photos: PhotoItem[];
+  subspecies: SubspeciesNode[];
+  low_confidence?: number;
+}
+
+interface SubspeciesNode {
+  name: string;
+  latin: string;
+  count: number;
+  stats: PhotoStats;
+}
+
+interface CombinationNode {
+  name: string;
+  chinese: string;
+  kind: "hybrid" | "pair";
+  species: string[];
+  count: number;
+  stats: PhotoStats;
 }
 
 interface GenusNode {
   name: string;
   count: number;
+  photographed_species: number;
+  total_species: number;
+  stats: PhotoStats;
   species: SpeciesNode[];
+  unidentified: number;
+  combinations: CombinationNode[];
 }
 
 interface FamilyNode {
   name: string;
   count: number;
+  photographed_species: number;
+  total_species: number;
+  stats: PhotoStats;
   genera: GenusNode[];
+  combinations: CombinationNode[];
 }
 
 interface OrderNode {
   name: string;
   count: number;
+  photographed_species: number;
+  total_species: number;
+  stats: PhotoStats;
   families: FamilyNode[];
+  combinations: CombinationNode[];
 }
 
 interface TaxonTree {
+  photographed_species: number;
+  total_species: number;
+  stats: PhotoStats;
   orders: OrderNode[];
+  combinations: CombinationNode[];
+  low_confidence: number;
 }
 
 interface ScanStats {
   total_files: number;
   matched_files: number;
   unmatched_files: number;
+  ignored_files: number;
+  vetoed_files: number;
+}
+
+interface VetoedMatch {
+  path: string;
+  latin: string;
+  reason: string;
+}
+
+interface PreviewResponse {
+  path: string;
+  placeholder: boolean;
+  error: string | null;
+}
+
+type TreeSort = "ioc" | "alphabetical" | "count";
+
+interface MatchOptions {
+  traditional: boolean;
+  pinyin: boolean;
+  pinyin_initials: boolean;
+  strip_accents: boolean;
+  alpha_codes: boolean;
+  english_names: boolean;
+  fuzzy_latin: boolean;
+}
+
+const EMPTY_STATS: PhotoStats = {
+  first_taken: null,
+  last_taken: null,
+  shooting_days: 0,
+  by_year: {}
+};
+
+const MATCH_OPTION_LABELS: [keyof MatchOptions, string][] = [
+  ["traditional", "文件名匹配繁体中文名"],
+  ["pinyin", "文件名匹配全拼（如 baitoubei）"],
+  ["pinyin_initials", "文件名匹配拼音首字母（如 btb，易误匹配）"],
+  ["strip_accents", "文件名匹配忽略变音符号（é = e）"],
+  ["alpha_codes", "文件名匹配环志代码（如 LVBU、PYCSIN）"],
+  ["english_names", "文件名匹配英文名（如 Light-vented Bulbul）"],
+  ["fuzzy_latin", "文件名匹配有一处拼写错误的拉丁名"]
+];
+
+const SOURCE_LABELS: Record<MatchSource, string> = {
+  override: "手动分类",
+  latin: "拉丁名",
+  chinese: "中文名",
+  alias: "别名",
+  code: "环志代码",
+  keyword: "英文名",
+  traditional: "繁体名",
+  pinyin: "全拼",
+  pinyin_initials: "拼音首字母",
+  genus: "属名",
+  fuzzy: "拉丁名（拼写纠正）",
+  folder: "文件夹名"
+};
+
+interface PhotoPage {
+  total: number;
+  offset: number;
+  photos: PhotoItem[];
+}
+
+const PHOTO_PAGE_SIZE = 200;
+
+interface SearchHit {
+  matched: "exact" | "prefix" | "substring" | "fuzzy";
+  field:
+    | "latin"
+    | "chinese"
+    | "traditional"
+    | "pinyin"
+    | "english"
+    | "other_language"
+    | "alias"
+    | "path";
+  text: string;
+  latin: string | null;
+  chinese: string | null;
+  photo: string | null;
+  count: number;
+  tree_path: string[];
 }
 
+const SEARCH_DEBOUNCE_MS = 150;
+
+type ExportFormat = "csv" | "xlsx" | "markdown";
+
+const EXPORT_EXTENSIONS: Record<ExportFormat, string> = {
+  csv: "csv",
+  xlsx: "xlsx",
+  markdown: "md"
+};
+
 interface ScanResponse {
   tree: TaxonTree;
   stats: ScanStats;
   total_species: number;
+  alias_problems: string[];
+  override_problems: string[];
+}
+
+function formatTakenAt(value: string | null): string {
+  return value ? value.replace("T", " ") : "—";
+}
+
+const RAW_EXTENSIONS = new Set([
+  "arw",
+  "cr2",
+  "cr3",
+  "dng",
+  "nef",
+  "nrw",
+  "orf",
+  "pef",
+  "raf",
+  "rw2",
+  "sr2",
+  "srf"
+]);
+
+function isRawPath(path: string): boolean {
+  const ext = path.split(".").pop()?.toLowerCase() ?? "";
+  return RAW_EXTENSIONS.has(ext);
+}
+
+function splitLines(text: string): string[] {
+  return text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean);
 }
 
 function toThumbnailSrc(path: string): string {
@@ -58,17 +251,90 @@ function toThumbnailSrc(path: string): string {
   }
 }
 
+function combinationAsSpecies(combination: CombinationNode): SpeciesNode {
+  return {
+    latin: combination.name,
+    chinese: combination.chinese,
+    count: combination.count,
+    stats: combination.stats,
+    photos: [],
+    subspecies: []
+  };
+}
+
+/** The species, `Genus sp.` or hybrid/pair node at a search hit's tree path. */
+function findTreeNode(tree: TaxonTree, path: string[]): SpeciesNode | null {
+  const [orderName, familyName, genusName, name] = path;
+  const combination = (combinations: CombinationNode[], combinationName: string) => {
+    const found = combinations.find((item) => item.name === combinationName);
+    return found ? combinationAsSpecies(found) : null;
+  };
+
+  if (path.length === 1) return combination(tree.combinations, orderName);
+  const order = tree.orders.find((item) => item.name === orderName);
+  if (!order) return null;
+  if (path.length === 2) return combination(order.combinations, familyName);
+  const family = order.families.find((item) => item.name === familyName);
+  if (!family) return null;
+  if (path.length === 3) return combination(family.combinations, genusName);
+  const genus = family.genera.find((item) => item.name === genusName);
+  if (!genus || path.length !== 4) return null;
+
+  const species = genus.species.find((item) => item.latin === name);
+  if (species) return species;
+  if (name === `${genus.name} sp.` && genus.unidentified > 0) {
+    return {
+      latin: name,
+      chinese: "",
+      count: genus.unidentified,
+      stats: EMPTY_STATS,
+      photos: [],
+      subspecies: []
+    };
+  }
+  return combination(genus.combinations, name);
+}
+
 export default function App() {
   const [iocPath, setIocPath] = useState("Multiling IOC 15.1_d.xlsx");
   const [cachePath, setCachePath] = useState("");
+  const [templates, setTemplates] = useState("");
+  const [neverMatch, setNeverMatch] = useState("");
+  const [vetoWords, setVetoWords] = useState("");
+  const [vetoed, setVetoed] = useState<VetoedMatch[]>([]);
   const [roots, setRoots] = useState<string[]>([]);
   const [scanResult, setScanResult] = useState<ScanResponse | null>(null);
   const [treeQuery, setTreeQuery] = useState("");
+  const [searchHits, setSearchHits] = useState<SearchHit[]>([]);
+  const [treeSort, setTreeSort] = useState<TreeSort>("ioc");
+  const [fullTree, setFullTree] = useState(false);
+  const [showSubspecies, setShowSubspecies] = useState(false);
+  const [minConfidence, setMinConfidence] = useState(0);
+  const [matchOptions, setMatchOptions] = useState<MatchOptions>({
+    traditional: false,
+    pinyin: false,
+    pinyin_initials: false,
+    strip_accents: false,
+    alpha_codes: false,
+    english_names: false,
+    fuzzy_latin: false
+  });
   const [selectedSpecies, setSelectedSpecies] = useState<SpeciesNode | null>(null);
   const [selectedPhoto, setSelectedPhoto] = useState<PhotoItem | null>(null);
+  const [photos, setPhotos] = useState<PhotoItem[]>([]);
+  const [photoTotal, setPhotoTotal] = useState(0);
+  const [isLoadingPhotos, setIsLoadingPhotos] = useState(false);
+  const [exportFormat, setExportFormat] = useState<ExportFormat>("csv");
+  const [exportYear, setExportYear] = useState("");
+  const [exportRoot, setExportRoot] = useState("");
+  const [exportPath, setExportPath] = useState<string[]>([]);
+  const [exportMessage, setExportMessage] = useState<string | null>(null);
+  const [galleryLinks, setGalleryLinks] = useState<"relative" | "absolute">("relative");
   const [thumbnailErrorMap, setThumbnailErrorMap] = useState<
     Record<string, boolean>
   >({});
+  const [overrideLatin, setOverrideLatin] = useState("");
+  const [overrideByContent, setOverrideByContent] = useState(false);
   const [isScanning, setIsScanning] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const rootPickerInputRef = useRef<HTMLInputElement | null>(null);
@@ -89,8 +355,54 @@ export default function App() {
 
   useEffect(() => {
     setThumbnailErrorMap({});
+    setPhotos([]);
+    setPhotoTotal(0);
+    if (selectedSpecies) {
+      void loadPhotos(selectedSpecies.latin, 0);
+    }
   }, [selectedSpecies]);
 
+  useEffect(() => {
+    const query = treeQuery.trim();
+    if (!query || !scanResult || !isTauri()) {
+      setSearchHits([]);
+      return;
+    }
+
+    let cancelled = false;
+    const timer = window.setTimeout(() => {
+      invoke<SearchHit[]>("search", { query })
+        .then((hits) => {
+          if (!cancelled) setSearchHits(hits);
+        })
+        .catch((err) => {
+          if (!cancelled) setError(String(err));
+        });
+    }, SEARCH_DEBOUNCE_MS);
+    return () => {
+      cancelled = true;
+      window.clearTimeout(timer);
+    };
+  }, [treeQuery, scanResult]);
+
+  const loadPhotos = async (latin: string, offset: number) => {
+    if (!isTauri()) return;
+    setIsLoadingPhotos(true);
+    try {
+      const page = await invoke<PhotoPage>("get_species_photos", {
+        latin,
+        offset,
+        limit: PHOTO_PAGE_SIZE
+      });
+      setPhotos((prev) => (offset === 0 ? page.photos : [...prev, ...page.photos]));
+      setPhotoTotal(page.total);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsLoadingPhotos(false);
+    }
+  };
+
   const rootsLabel = useMemo(() => {
     if (roots.length === 0) return "0 个目录";
     return `${roots.length} 个目录`;
@@ -163,16 +475,33 @@ export default function App() {
     setError(null);
     setSelectedSpecies(null);
     setSelectedPhoto(null);
+    setExportPath([]);
     try {
       const effectiveCachePath = cachePath || "cache.json";
       const response = await invoke<ScanResponse>("scan", {
         request: {
           roots,
+          sort: treeSort,
+          full_tree: fullTree,
+          subspecies: showSubspecies,
+          min_confidence: minConfidence,
+          ...matchOptions,
+          templates: splitLines(templates),
+          never_match: splitLines(neverMatch),
+          veto_words: vetoWords
+            .split(/[,，\s]+/)
+            .map((word) => word.trim())
+            .filter(Boolean),
           ioc_path: iocPath,
           cache_path: effectiveCachePath
         }
       });
       setScanResult(response);
+      setVetoed(
+        response.stats.vetoed_files > 0
+          ? await invoke<VetoedMatch[]>("get_vetoed", { offset: 0, limit: 200 })
+          : []
+      );
     } catch (err) {
       setError(String(err));
     } finally {
@@ -180,6 +509,76 @@ export default function App() {
     }
   };
 
+  const handleExportLifeList = async () => {
+    setError(null);
+    setExportMessage(null);
+    try {
+      const extension = EXPORT_EXTENSIONS[exportFormat];
+      const output = await save({
+        title: "导出鸟种清单",
+        defaultPath: `life-list.${extension}`,
+        filters: [{ name: exportFormat.toUpperCase(), extensions: [extension] }]
+      });
+      if (!output) return;
+      const year = exportYear.trim() ? Number(exportYear.trim()) : null;
+      const rows = await invoke<number>("export_life_list", {
+        request: {
+          output,
+          format: exportFormat,
+          year: Number.isFinite(year) ? year : null,
+          root: exportRoot || null
+        }
+      });
+      setExportMessage(`已导出 ${rows} 个鸟种：${output}`);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleExportDarwinCore = async () => {
+    setError(null);
+    setExportMessage(null);
+    try {
+      const output = await save({
+        title: "导出 Darwin Core Archive",
+        defaultPath: "birdindex2-dwca.zip",
+        filters: [{ name: "Darwin Core Archive", extensions: ["zip"] }]
+      });
+      if (!output) return;
+      const year = exportYear.trim() ? Number(exportYear.trim()) : null;
+      const records = await invoke<number>("export_darwin_core", {
+        request: {
+          output,
+          path: exportPath,
+          year: Number.isFinite(year) ? year : null,
+          root: exportRoot || null
+        }
+      });
+      setExportMessage(`已导出 ${records} 条观测记录：${output}`);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleExportGallery = async () => {
+    setError(null);
+    setExportMessage(null);
+    try {
+      const output = await open({
+        directory: true,
+        multiple: false,
+        title: "选择 HTML 图库输出目录"
+      });
+      if (!output || Array.isArray(output)) return;
+      const pages = await invoke<number>("export_gallery", {
+        request: { output, links: galleryLinks }
+      });
+      setExportMessage(`已生成 ${pages} 个物种页面：${output}`);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
   const handleReveal = async () => {
     if (!selectedPhoto) return;
     try {
@@ -189,6 +588,41 @@ export default function App() {
     }
   };
 
+  const handleSetOverride = async (ignore: boolean) => {
+    if (!selectedPhoto) return;
+    setError(null);
+    try {
+      await invoke("set_override", {
+        request: {
+          path: selectedPhoto.path,
+          ...(ignore
+            ? { action: "ignore" }
+            : { action: "species", latin: overrideLatin.trim() }),
+          by_content: overrideByContent
+        }
+      });
+      setOverrideLatin("");
+      await handleScan();
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleClearOverride = async () => {
+    if (!selectedPhoto) return;
+    setError(null);
+    try {
+      const removed = await invoke<boolean>("clear_override", { path: selectedPhoto.path });
+      if (removed) {
+        await handleScan();
+      } else {
+        setError("该照片没有手动分类");
+      }
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
   const handleOpen = async (path: string) => {
     try {
       await invoke("open_file", { path });
@@ -197,6 +631,26 @@ export default function App() {
     }
   };
 
+  const handleSelectHit = (hit: SearchHit) => {
+    const species = scanResult ? findTreeNode(scanResult.tree, hit.tree_path) : null;
+
+    if (hit.photo && !species) {
+      void handleOpen(hit.photo);
+      return;
+    }
+    if (!species) return;
+    setSelectedSpecies(species);
+    setSelectedPhoto(
+      hit.photo
+        ? {
+            path: hit.photo,
+            file_name: hit.photo.split(/[\\/]/).pop() ?? hit.photo,
+            taken_at: null
+          }
+        : null
+    );
+  };
+
   const handleThumbnailError = (path: string) => {
     setThumbnailErrorMap((prev) => {
       if (prev[path]) return prev;
@@ -231,6 +685,82 @@ export default function App() {
             placeholder="Multiling IOC 15.1_d.xlsx"
           />
         </label>
+        <label>
+          分类树排序
+          <select
+            value={treeSort}
+            onChange={(event) => setTreeSort(event.target.value as TreeSort)}
+          >
+            <option value="ioc">IOC 顺序</option>
+            <option value="alphabetical">字母顺序</option>
+            <option value="count">照片数量</option>
+          </select>
+        </label>
+        <label className="checkbox">
+          <input
+            type="checkbox"
+            checked={fullTree}
+            onChange={(event) => setFullTree(event.target.checked)}
+          />
+          显示完整 IOC 树（包含未拍摄物种）
+        </label>
+        <label className="checkbox">
+          <input
+            type="checkbox"
+            checked={showSubspecies}
+            onChange={(event) => setShowSubspecies(event.target.checked)}
+          />
+          显示亚种层级
+        </label>
+        <label>
+          最低置信度（0–100，低于此值的照片不计入）
+          <input
+            type="number"
+            min={0}
+            max={100}
+            value={minConfidence}
+            onChange={(event) =>
+              setMinConfidence(Math.min(100, Math.max(0, Number(event.target.value) || 0)))
+            }
+          />
+        </label>
+        {MATCH_OPTION_LABELS.map(([key, label]) => (
+          <label key={key} className="checkbox">
+            <input
+              type="checkbox"
+              checked={matchOptions[key]}
+              onChange={(event) =>
+                setMatchOptions((prev) => ({ ...prev, [key]: event.target.checked }))
+              }
+            />
+            {label}
+          </label>
+        ))}
+        <label>
+          文件名模板（每行一个，如 {"{date}_{location}_{species}_{seq}"}）
+          <textarea
+            rows={2}
+            value={templates}
+            onChange={(event) => setTemplates(event.target.value)}
+            placeholder="{date}_{location}_{species}_{seq}"
+          />
+        </label>
+        <label>
+          永不匹配的文本（每行一个，如地名）
+          <textarea
+            rows={2}
+            value={neverMatch}
+            onChange={(event) => setNeverMatch(event.target.value)}
+          />
+        </label>
+        <label>
+          否决词（文件名含这些词时不归类）
+          <input
+            value={vetoWords}
+            onChange={(event) => setVetoWords(event.target.value)}
+            placeholder="not, vs, cf."
+          />
+        </label>
         <div className="root-picker">
           <div className="root-header">
             <div>
@@ -276,11 +806,99 @@ export default function App() {
             <span>扫描文件：{scanResult.stats.total_files}</span>
             <span>命中：{scanResult.stats.matched_files}</span>
             <span>未命中：{scanResult.stats.unmatched_files}</span>
+            {scanResult.stats.ignored_files > 0 && (
+              <span>已忽略：{scanResult.stats.ignored_files}</span>
+            )}
+            {scanResult.stats.vetoed_files > 0 && (
+              <span>被否决：{scanResult.stats.vetoed_files}</span>
+            )}
+            {scanResult.tree.low_confidence > 0 && (
+              <span>低置信度：{scanResult.tree.low_confidence}</span>
+            )}
             <span>IOC 物种数：{scanResult.total_species}</span>
+            <span>
+              已拍摄物种：{scanResult.tree.photographed_species}/
+              {scanResult.tree.total_species}
+            </span>
           </div>
         ) : (
           <div className="stats">等待扫描</div>
         )}
+        {vetoed.length > 0 ? (
+          <details className="inline-note vetoed-list">
+            <summary>被否决的匹配（计入未命中）</summary>
+            {vetoed.map((item) => (
+              <div key={item.path}>
+                {item.path} → {item.latin}（{item.reason}）
+              </div>
+            ))}
+          </details>
+        ) : null}
+        {scanResult && scanResult.alias_problems.length > 0 ? (
+          <div className="inline-note">
+            别名表（aliases.csv）有 {scanResult.alias_problems.length} 行未生效：
+            {scanResult.alias_problems.join("；")}
+          </div>
+        ) : null}
+        {scanResult && scanResult.override_problems.length > 0 ? (
+          <div className="inline-note">
+            有 {scanResult.override_problems.length} 条手动归类的学名不在 IOC 名录中，已改用文件名匹配：
+            {scanResult.override_problems.join("；")}
+          </div>
+        ) : null}
+        {scanResult ? (
+          <div className="export-bar">
+            <select
+              value={exportFormat}
+              onChange={(event) => setExportFormat(event.target.value as ExportFormat)}
+            >
+              <option value="csv">CSV</option>
+              <option value="xlsx">XLSX</option>
+              <option value="markdown">Markdown</option>
+            </select>
+            <input
+              value={exportYear}
+              onChange={(event) => setExportYear(event.target.value)}
+              placeholder="年份（可选）"
+              inputMode="numeric"
+            />
+            <select
+              value={exportRoot}
+              onChange={(event) => setExportRoot(event.target.value)}
+            >
+              <option value="">全部根目录</option>
+              {roots.map((root) => (
+                <option key={root} value={root}>
+                  {root}
+                </option>
+              ))}
+            </select>
+            <button className="ghost" onClick={handleExportLifeList}>
+              导出鸟种清单
+            </button>
+            <button className="ghost" onClick={handleExportDarwinCore}>
+              导出 Darwin Core（{exportPath.length > 0 ? exportPath.join(" › ") : "全部"}）
+            </button>
+            {exportPath.length > 0 ? (
+              <button className="ghost" onClick={() => setExportPath([])}>
+                导出全部
+              </button>
+            ) : null}
+            <select
+              value={galleryLinks}
+              onChange={(event) =>
+                setGalleryLinks(event.target.value as "relative" | "absolute")
+              }
+            >
+              <option value="relative">相对链接</option>
+              <option value="absolute">绝对链接</option>
+            </select>
+            <button className="ghost" onClick={handleExportGallery}>
+              导出 HTML 图库
+            </button>
+            {exportMessage ? <span>{exportMessage}</span> : null}
+          </div>
+        ) : null}
         {error ? <div className="error">{error}</div> : null}
       </section>
 
@@ -294,15 +912,19 @@ export default function App() {
                   type="search"
                   value={treeQuery}
                   onChange={(event) => setTreeQuery(event.target.value)}
-                  placeholder="搜索物种（中文或拉丁名）"
-                  aria-label="搜索物种（中文或拉丁名）"
+                  placeholder="搜索物种或照片（中文、繁体、拼音、拉丁名、英文名、路径）"
+                  aria-label="搜索物种或照片（中文、繁体、拼音、拉丁名、英文名、路径）"
                 />
               </div>
-              <TreeView
-                tree={scanResult.tree}
-                query={treeQuery}
-                onSelect={setSelectedSpecies}
-              />
+              {treeQuery.trim() ? (
+                <SearchResults hits={searchHits} onSelect={handleSelectHit} />
+              ) : (
+                <TreeView
+                  tree={scanResult.tree}
+                  onSelect={setSelectedSpecies}
+                  onSelectPath={setExportPath}
+                />
+              )}
             </>
           ) : (
             <div className="empty">尚未生成分类树</div>
@@ -313,7 +935,7 @@ export default function App() {
           <h2>缩略图</h2>
           {selectedSpecies ? (
             <div className="grid">
-              {selectedSpecies.photos.map((photo) => (
+              {photos.map((photo) => (
                 <button
                   key={photo.path}
                   className={
@@ -333,15 +955,25 @@ export default function App() {
                       无法预览
                     </div>
                   ) : (
-                    <img
-                      src={toThumbnailSrc(photo.path)}
-                      alt={photo.file_name}
+                    <Thumbnail
+                      photo={photo}
                       onError={() => handleThumbnailError(photo.path)}
                     />
                   )}
                   <span>{photo.file_name}</span>
                 </button>
               ))}
+              {photos.length < photoTotal ? (
+                <button
+                  className="ghost"
+                  disabled={isLoadingPhotos}
+                  onClick={() => loadPhotos(selectedSpecies.latin, photos.length)}
+                >
+                  {isLoadingPhotos
+                    ? "加载中..."
+                    : `加载更多（${photos.length}/${photoTotal}）`}
+                </button>
+              ) : null}
             </div>
           ) : (
             <div className="empty">请选择一个物种</div>
@@ -358,6 +990,35 @@ export default function App() {
                   : selectedSpecies.latin}
               </div>
               <div className="meta-row">数量：{selectedSpecies.count}</div>
+              <div className="meta-row">
+                首次拍摄：{formatTakenAt(selectedSpecies.stats.first_taken)}
+              </div>
+              <div className="meta-row">
+                最近拍摄：{formatTakenAt(selectedSpecies.stats.last_taken)}
+              </div>
+              <div className="meta-row">
+                拍摄天数：{selectedSpecies.stats.shooting_days}
+              </div>
+              <div className="meta-row">
+                按年：
+                {Object.entries(selectedSpecies.stats.by_year)
+                  .map(([year, count]) => `${year}（${count}）`)
+                  .join("，") || "—"}
+              </div>
+              {selectedPhoto?.subspecies && (
+                <div className="meta-row">亚种：{selectedPhoto.subspecies}</div>
+              )}
+              {selectedPhoto?.location && (
+                <div className="meta-row">地点：{selectedPhoto.location}</div>
+              )}
+              {selectedPhoto?.sequence && (
+                <div className="meta-row">序号：{selectedPhoto.sequence}</div>
+              )}
+              {selectedPhoto && (
+                <div className="meta-row">
+                  置信度：{selectedPhoto.confidence}（{SOURCE_LABELS[selectedPhoto.source]}）
+                </div>
+              )}
               <div className="meta-row">
                 {selectedPhoto ? selectedPhoto.path : "选择一张照片查看路径"}
               </div>
@@ -368,6 +1029,44 @@ export default function App() {
               >
                 定位到文件夹
               </button>
+              {selectedPhoto && (
+                <div className="override-bar">
+                  <input
+                    value={overrideLatin}
+                    placeholder="改为物种（拉丁名）"
+                    onChange={(event) => setOverrideLatin(event.target.value)}
+                  />
+                  <button
+                    className="ghost small"
+                    onClick={() => handleSetOverride(false)}
+                    disabled={!overrideLatin.trim() || isScanning}
+                  >
+                    手动分类
+                  </button>
+                  <button
+                    className="ghost small"
+                    onClick={() => handleSetOverride(true)}
+                    disabled={isScanning}
+                  >
+                    忽略此照片
+                  </button>
+                  <button
+                    className="ghost small"
+                    onClick={handleClearOverride}
+                    disabled={isScanning}
+                  >
+                    清除手动分类
+                  </button>
+                  <label className="checkbox">
+                    <input
+                      type="checkbox"
+                      checked={overrideByContent}
+                      onChange={(event) => setOverrideByContent(event.target.checked)}
+                    />
+                    按文件内容记录（移动或改名后仍生效）
+                  </label>
+                </div>
+              )}
             </div>
           ) : (
             <div className="empty">尚未选择物种</div>
@@ -378,85 +1077,223 @@ export default function App() {
   );
 }
 
-function TreeView({
-  tree,
-  query,
-  onSelect
+function Thumbnail({
+  photo,
+  onError
 }: {
-  tree: TaxonTree;
-  query: string;
-  onSelect: (species: SpeciesNode) => void;
+  photo: PhotoItem;
+  onError: () => void;
 }) {
-  const normalizedQuery = query.trim().toLowerCase();
-  const hasQuery = normalizedQuery.length > 0;
-
-  const filteredOrders = useMemo(() => {
-    if (!normalizedQuery) return tree.orders;
-
-    return tree.orders
-      .map((order) => {
-        const families = order.families
-          .map((family) => {
-            const genera = family.genera
-              .map((genus) => {
-                const species = genus.species.filter((item) => {
-                  return (
-                    item.chinese.toLowerCase().includes(normalizedQuery) ||
-                    item.latin.toLowerCase().includes(normalizedQuery)
-                  );
-                });
-
-                if (species.length === 0) return null;
-                return { ...genus, species };
-              })
-              .filter((genus): genus is GenusNode => genus !== null);
-
-            if (genera.length === 0) return null;
-            return { ...family, genera };
-          })
-          .filter((family): family is FamilyNode => family !== null);
-
-        if (families.length === 0) return null;
-        return { ...order, families };
+  const raw = isRawPath(photo.path);
+  const [src, setSrc] = useState(raw ? "" : toThumbnailSrc(photo.path));
+
+  useEffect(() => {
+    if (!raw) {
+      setSrc(toThumbnailSrc(photo.path));
+      return;
+    }
+    if (!isTauri()) {
+      onError();
+      return;
+    }
+
+    let cancelled = false;
+    invoke<PreviewResponse>("preview", { path: photo.path })
+      .then((preview) => {
+        if (cancelled) return;
+        if (preview.error) {
+          console.warn(`RAW 预览失败：${photo.path}: ${preview.error}`);
+        }
+        setSrc(toThumbnailSrc(preview.path));
       })
-      .filter((order): order is OrderNode => order !== null);
-  }, [tree, normalizedQuery]);
+      .catch(() => {
+        if (!cancelled) onError();
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [photo.path]);
+
+  if (!src) {
+    return <div className="photo-fallback">加载中</div>;
+  }
+
+  return <img src={src} alt={photo.file_name} onError={onError} />;
+}
+
+const MATCH_LABELS: Record<SearchHit["matched"], string> = {
+  exact: "完全匹配",
+  prefix: "前缀",
+  substring: "包含",
+  fuzzy: "近似"
+};
 
-  if (hasQuery && filteredOrders.length === 0) {
-    return <div className="empty">未找到匹配物种</div>;
+function SearchResults({
+  hits,
+  onSelect
+}: {
+  hits: SearchHit[];
+  onSelect: (hit: SearchHit) => void;
+}) {
+  if (hits.length === 0) {
+    return <div className="empty">未找到匹配物种或照片</div>;
   }
 
+  return (
+    <div className="species-list search-results">
+      {hits.map((hit) => (
+        <button
+          key={`${hit.photo ?? ""}|${hit.latin ?? ""}`}
+          className={hit.count > 0 || hit.photo ? "species" : "species empty-species"}
+          onClick={() => onSelect(hit)}
+        >
+          <span className="search-match">{MATCH_LABELS[hit.matched]}</span>
+          {hit.photo
+            ? hit.photo
+            : `${hit.chinese ? `${hit.chinese} ` : ""}${hit.latin} (${hit.count})`}
+          {hit.tree_path.length > 1 ? (
+            <span className="search-path">{hit.tree_path.slice(0, -1).join(" › ")}</span>
+          ) : null}
+        </button>
+      ))}
+    </div>
+  );
+}
+
+function TreeView({
+  tree,
+  onSelect,
+  onSelectPath
+}: {
+  tree: TaxonTree;
+  onSelect: (species: SpeciesNode) => void;
+  /** Picks the node a Darwin Core export covers. */
+  onSelectPath: (path: string[]) => void;
+}) {
   return (
     <div className="tree-root">
-      {filteredOrders.map((order) => (
+      {tree.orders.map((order) => (
         <details key={order.name} open>
-          <summary>{order.name}</summary>
+          <summary onClick={() => onSelectPath([order.name])}>
+            {order.name} ({order.photographed_species}/{order.total_species})
+          </summary>
           {order.families.map((family) => (
-            <details key={family.name} className="level" open={hasQuery || undefined}>
-              <summary>{family.name}</summary>
+            <details key={family.name} className="level">
+              <summary onClick={() => onSelectPath([order.name, family.name])}>
+                {family.name} ({family.photographed_species}/{family.total_species})
+              </summary>
               {family.genera.map((genus) => (
-                <details key={genus.name} className="level" open={hasQuery || undefined}>
-                  <summary>{genus.name}</summary>
+                <details key={genus.name} className="level">
+                  <summary onClick={() => onSelectPath([order.name, family.name, genus.name])}>
+                    {genus.name} ({genus.photographed_species}/{genus.total_species})
+                  </summary>
                   <div className="species-list">
                     {genus.species.map((species) => (
+                      <div key={species.latin}>
+                        <button
+                          className={species.count > 0 ? "species" : "species empty-species"}
+                          onClick={() => {
+                            onSelect(species);
+                            onSelectPath([order.name, family.name, genus.name, species.latin]);
+                          }}
+                        >
+                          {species.chinese
+                            ? `${species.chinese} ${species.latin}`
+                            : species.latin}{" "}
+                          ({species.count}
+                          {species.low_confidence
+                            ? `，低置信度 ${species.low_confidence}`
+                            : ""}
+                          )
+                        </button>
+                        {species.subspecies.length > 0 && (
+                          <div className="subspecies-list">
+                            {species.subspecies.map((sub) => (
+                              <button
+                                key={sub.latin}
+                                className={sub.count > 0 ? "species" : "species empty-species"}
+                                onClick={() =>
+                                  onSelect({
+                                    ...sub,
+                                    chinese: species.chinese,
+                                    photos: [],
+                                    subspecies: []
+                                  })
+                                }
+                              >
+                                {sub.latin} ({sub.count})
+                              </button>
+                            ))}
+                          </div>
+                        )}
+                      </div>
+                    ))}
+                    {genus.unidentified > 0 && (
                       <button
-                        key={species.latin}
                         className="species"
-                        onClick={() => onSelect(species)}
+                        onClick={() =>
+                          onSelect({
+                            latin: `${genus.name} sp.`,
+                            chinese: "",
+                            count: genus.unidentified,
+                            stats: EMPTY_STATS,
+                            photos: [],
+                            subspecies: []
+                          })
+                        }
                       >
-                        {species.chinese
-                          ? `${species.chinese} ${species.latin}`
-                          : species.latin}{" "}
-                        ({species.count})
+                        {genus.name} sp. ({genus.unidentified})
                       </button>
-                    ))}
+                    )}
+                    <CombinationButtons
+                      combinations={genus.combinations}
+                      onSelect={onSelect}
+                    />
                   </div>
                 </details>
               ))}
+              <div className="species-list">
+                <CombinationButtons
+                  combinations={family.combinations}
+                  onSelect={onSelect}
+                />
+              </div>
             </details>
           ))}
+          <div className="species-list">
+            <CombinationButtons combinations={order.combinations} onSelect={onSelect} />
+          </div>
         </details>
       ))}
+      <div className="species-list">
+        <CombinationButtons combinations={tree.combinations} onSelect={onSelect} />
+      </div>
     </div>
   );
 }
+
+function CombinationButtons({
+  combinations,
+  onSelect
+}: {
+  combinations: CombinationNode[];
+  onSelect: (species: SpeciesNode) => void;
+}) {
+  return (
+    <>
+      {combinations.map((combination) => (
+        <button
+          key={combination.name}
+          className="species"
+          title={combination.kind === "hybrid" ? "杂交" : "二选一"}
+          onClick={() => onSelect(combinationAsSpecies(combination))}
+        >
+          {combination.chinese
+            ? `${combination.chinese} ${combination.name}`
+            : combination.name}{" "}
+          ({combination.count})
+        </button>
+      ))}
+    </>
+  );
+}